@@ -0,0 +1,104 @@
+use rand::Rng;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct BackoffPolicy {
+    initial: Duration,
+    max: Duration,
+    multiplier: f64,
+    // Fraction of each delay that may be added or removed at random, from 0.0 to 1.0.
+    jitter: f64,
+    attempt: u32,
+}
+
+impl BackoffPolicy {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            multiplier: 2.0,
+            jitter: 0.0,
+            attempt: 0,
+        }
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let growth = self.multiplier.powi(self.attempt.min(i32::MAX as u32) as i32);
+        let max = self.max.as_secs_f64();
+        self.attempt = self.attempt.saturating_add(1);
+
+        let mut delay = if self.initial.is_zero() {
+            0.0
+        } else {
+            (self.initial.as_secs_f64() * growth).min(max)
+        };
+        if self.jitter > 0.0 {
+            delay *= 1.0 + rand::thread_rng().gen_range(-self.jitter..=self.jitter);
+        }
+
+        Duration::from_secs_f64(delay.clamp(0.0, max))
+    }
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_secs(30)).with_jitter(0.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_grow_until_capped() {
+        let mut policy = BackoffPolicy::new(Duration::from_secs(1), Duration::from_secs(10));
+        let delays: Vec<_> = (0..6).map(|_| policy.next_delay().as_secs()).collect();
+
+        assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+        assert_eq!(policy.attempt(), 6);
+
+        policy.reset();
+        assert_eq!(policy.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let mut policy = BackoffPolicy::new(Duration::from_secs(4), Duration::from_secs(100))
+            .with_multiplier(1.0)
+            .with_jitter(0.25);
+
+        for _ in 0..100 {
+            let delay = policy.next_delay();
+            assert!(delay >= Duration::from_secs(3) && delay <= Duration::from_secs(5));
+        }
+    }
+
+    #[test]
+    fn jitter_never_exceeds_the_cap() {
+        let mut policy =
+            BackoffPolicy::new(Duration::from_secs(10), Duration::from_secs(10)).with_jitter(1.0);
+
+        for _ in 0..100 {
+            assert!(policy.next_delay() <= Duration::from_secs(10));
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use super::TargetSelector;
+use serde::{Deserialize, Serialize};
+
+crate::command! {
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct ClearCommand {
+        pub target: TargetSelector,
+    }
+    => "clear {}", target;
+    pub struct ClearCommandResponse {}
+}
+
+impl ClearCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T) -> Self {
+        Self {
+            target: target.into(),
+        }
+    }
+
+    pub fn everyone() -> Self {
+        Self::new(TargetSelector::players())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command_line;
+
+    #[test]
+    fn command_lines() {
+        assert_eq!(command_line(ClearCommand::everyone()), "clear @a");
+        assert_eq!(command_line(ClearCommand::new("Steve")), "clear Steve");
+    }
+}
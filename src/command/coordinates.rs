@@ -0,0 +1,136 @@
+use crate::{event::Position, Error};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinate {
+    Absolute(f64),
+    // Offset from the executor's position, written `~`.
+    Relative(f64),
+    // Offset along the executor's facing direction, written `^`.
+    Local(f64),
+}
+
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (prefix, offset) = match *self {
+            Coordinate::Absolute(value) => return write!(f, "{}", value),
+            Coordinate::Relative(offset) => ("~", offset),
+            Coordinate::Local(offset) => ("^", offset),
+        };
+
+        if offset == 0.0 {
+            f.write_str(prefix)
+        } else {
+            write!(f, "{}{}", prefix, offset)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl Coordinates {
+    pub fn absolute(x: f64, y: f64, z: f64) -> Self {
+        Self {
+            x: Coordinate::Absolute(x),
+            y: Coordinate::Absolute(y),
+            z: Coordinate::Absolute(z),
+        }
+    }
+
+    pub fn relative(x: f64, y: f64, z: f64) -> Self {
+        Self {
+            x: Coordinate::Relative(x),
+            y: Coordinate::Relative(y),
+            z: Coordinate::Relative(z),
+        }
+    }
+
+    pub fn local(left: f64, up: f64, forward: f64) -> Self {
+        Self {
+            x: Coordinate::Local(left),
+            y: Coordinate::Local(up),
+            z: Coordinate::Local(forward),
+        }
+    }
+
+    pub fn here() -> Self {
+        Self::relative(0.0, 0.0, 0.0)
+    }
+}
+
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.z)
+    }
+}
+
+impl From<Position> for Coordinates {
+    fn from(value: Position) -> Self {
+        Self::absolute(value.x, value.y, value.z)
+    }
+}
+
+// Only absolute coordinates name a fixed point; the dimension is left unknown.
+impl TryFrom<Coordinates> for Position {
+    type Error = Error;
+
+    fn try_from(value: Coordinates) -> Result<Self, Self::Error> {
+        match (value.x, value.y, value.z) {
+            (Coordinate::Absolute(x), Coordinate::Absolute(y), Coordinate::Absolute(z)) => Ok(Self {
+                x,
+                y,
+                z,
+                dimension: None,
+            }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates_render_their_prefixes() {
+        assert_eq!(
+            Coordinates::absolute(1.0, 64.5, -3.0).to_string(),
+            "1 64.5 -3"
+        );
+        assert_eq!(
+            Coordinates::relative(0.0, 1.0, -2.0).to_string(),
+            "~ ~1 ~-2"
+        );
+        assert_eq!(Coordinates::local(0.0, 0.0, 5.0).to_string(), "^ ^ ^5");
+        assert_eq!(Coordinates::here().to_string(), "~ ~ ~");
+    }
+
+    #[test]
+    fn positions_convert_to_absolute_coordinates() {
+        let position = Position {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            dimension: Some(2),
+        };
+        let coordinates = Coordinates::from(position);
+        assert_eq!(coordinates, Coordinates::absolute(1.0, 2.0, 3.0));
+
+        let position = Position::try_from(coordinates).unwrap();
+        assert_eq!((position.x, position.y, position.z), (1.0, 2.0, 3.0));
+        assert_eq!(position.dimension, None);
+    }
+
+    #[test]
+    fn relative_coordinates_are_not_positions() {
+        assert!(matches!(
+            Position::try_from(Coordinates::here()),
+            Err(Error::InvalidType)
+        ));
+    }
+}
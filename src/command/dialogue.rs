@@ -0,0 +1,149 @@
+use super::{escape::quote_if_needed, Command, CommandMeta, TargetSelector};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+// Without a scene, `Open` shows the NPC's current one; without players, `Change`
+// applies to everyone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum DialogueCommand {
+    Open {
+        npc: TargetSelector,
+        player: TargetSelector,
+        scene_name: Option<String>,
+    },
+    Change {
+        npc: TargetSelector,
+        scene_name: String,
+        players: Option<TargetSelector>,
+    },
+}
+
+impl DialogueCommand {
+    pub fn open<N: Into<TargetSelector>, P: Into<TargetSelector>>(
+        npc: N,
+        player: P,
+        scene_name: Option<&str>,
+    ) -> Self {
+        Self::Open {
+            npc: npc.into(),
+            player: player.into(),
+            scene_name: scene_name.map(str::to_string),
+        }
+    }
+
+    pub fn change<N: Into<TargetSelector>>(npc: N, scene_name: &str) -> Self {
+        Self::Change {
+            npc: npc.into(),
+            scene_name: scene_name.to_string(),
+            players: None,
+        }
+    }
+
+    // Only applies to `Change`.
+    pub fn with_players<T: Into<TargetSelector>>(mut self, target: T) -> Self {
+        if let Self::Change { players, .. } = &mut self {
+            *players = Some(target.into());
+        }
+        self
+    }
+}
+
+pub struct DialogueCommandResponse {
+    pub meta: CommandMeta,
+}
+
+impl Command for DialogueCommand {
+    type Response = DialogueCommandResponse;
+}
+
+impl From<DialogueCommand> for CommandRequestPacket {
+    fn from(value: DialogueCommand) -> Self {
+        Self::new(&match value {
+            DialogueCommand::Open {
+                npc,
+                player,
+                scene_name: Some(scene_name),
+            } => format!(
+                "dialogue open {} {} {}",
+                npc,
+                player,
+                quote_if_needed(&scene_name)
+            ),
+            DialogueCommand::Open {
+                npc,
+                player,
+                scene_name: None,
+            } => format!("dialogue open {} {}", npc, player),
+            DialogueCommand::Change {
+                npc,
+                scene_name,
+                players: Some(players),
+            } => format!(
+                "dialogue change {} {} {}",
+                npc,
+                quote_if_needed(&scene_name),
+                players
+            ),
+            DialogueCommand::Change {
+                npc,
+                scene_name,
+                players: None,
+            } => format!("dialogue change {} {}", npc, quote_if_needed(&scene_name)),
+        })
+    }
+}
+
+impl TryFrom<CommandResponsePacket> for DialogueCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        Ok(Self {
+            meta: CommandMeta::from(&value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command_line;
+
+    fn npc() -> TargetSelector {
+        TargetSelector::all_entities().type_("npc").count(1)
+    }
+
+    #[test]
+    fn open_forms() {
+        assert_eq!(
+            command_line(DialogueCommand::open(npc(), "Steve", Some("intro"))),
+            "dialogue open @e[type=npc,c=1] Steve intro"
+        );
+        assert_eq!(
+            command_line(DialogueCommand::open(npc(), "Steve", None)),
+            "dialogue open @e[type=npc,c=1] Steve"
+        );
+    }
+
+    #[test]
+    fn change_forms() {
+        assert_eq!(
+            command_line(DialogueCommand::change(npc(), "second act")),
+            r#"dialogue change @e[type=npc,c=1] "second act""#
+        );
+        assert_eq!(
+            command_line(
+                DialogueCommand::change(npc(), "outro").with_players(TargetSelector::players())
+            ),
+            "dialogue change @e[type=npc,c=1] outro @a"
+        );
+    }
+
+    #[test]
+    fn players_only_apply_to_change() {
+        let command = DialogueCommand::open(npc(), "Steve", None).with_players("Alex");
+        assert_eq!(command, DialogueCommand::open(npc(), "Steve", None));
+    }
+}
@@ -0,0 +1,117 @@
+use super::{Command, CommandMeta, parse_enum_from_message};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Peaceful => "peaceful",
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+        })
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "peaceful" => Ok(Self::Peaceful),
+            "easy" => Ok(Self::Easy),
+            "normal" => Ok(Self::Normal),
+            "hard" => Ok(Self::Hard),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+// Not a query either: `/difficulty` needs an argument, so sending this changes the
+// world's difficulty. The response echoes the difficulty from the confirmation.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultyCommand {
+    pub difficulty: Difficulty,
+}
+
+impl DifficultyCommand {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { difficulty }
+    }
+}
+
+pub struct DifficultyCommandResponse {
+    pub meta: CommandMeta,
+    pub difficulty: Difficulty,
+}
+
+impl Command for DifficultyCommand {
+    type Response = DifficultyCommandResponse;
+}
+
+impl From<DifficultyCommand> for CommandRequestPacket {
+    fn from(value: DifficultyCommand) -> Self {
+        Self::new(format!("difficulty {}", value.difficulty).as_str())
+    }
+}
+
+// Replies read "Set game difficulty to Normal".
+impl TryFrom<CommandResponsePacket> for DifficultyCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+
+        Ok(Self {
+            meta,
+            difficulty: parse_enum_from_message(&message).ok_or(Error::InvalidType)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::status_reply;
+
+    fn parse(message: &str) -> Result<DifficultyCommandResponse> {
+        status_reply(0, message).try_into()
+    }
+
+    #[test]
+    fn difficulty_is_parsed() {
+        let response = parse("Set game difficulty to Normal").unwrap();
+        assert_eq!(response.difficulty, Difficulty::Normal);
+    }
+
+    #[test]
+    fn missing_message_is_rejected() {
+        let response = DifficultyCommandResponse::try_from(CommandResponsePacket::default());
+        assert!(matches!(
+            response,
+            Err(Error::MissingField("statusMessage"))
+        ));
+    }
+
+    #[test]
+    fn request_names_difficulty() {
+        let packet = CommandRequestPacket::from(DifficultyCommand::new(Difficulty::Hard));
+        assert_eq!(packet.command_line, "difficulty hard");
+    }
+}
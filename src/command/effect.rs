@@ -0,0 +1,91 @@
+use super::{Command, CommandMeta, TargetSelector};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum EffectCommand {
+    Give {
+        target: TargetSelector,
+        effect: String,
+        seconds: u32,
+        amplifier: u8,
+    },
+    Clear {
+        target: TargetSelector,
+    },
+}
+
+impl EffectCommand {
+    pub fn give<T: Into<TargetSelector>>(
+        target: T,
+        effect: &str,
+        seconds: u32,
+        amplifier: u8,
+    ) -> Self {
+        Self::Give {
+            target: target.into(),
+            effect: effect.to_string(),
+            seconds,
+            amplifier,
+        }
+    }
+
+    pub fn clear_all<T: Into<TargetSelector>>(target: T) -> Self {
+        Self::Clear {
+            target: target.into(),
+        }
+    }
+}
+
+pub struct EffectCommandResponse {
+    pub meta: CommandMeta,
+}
+
+impl Command for EffectCommand {
+    type Response = EffectCommandResponse;
+}
+
+impl From<EffectCommand> for CommandRequestPacket {
+    fn from(value: EffectCommand) -> Self {
+        Self::new(&match value {
+            EffectCommand::Give {
+                target,
+                effect,
+                seconds,
+                amplifier,
+            } => format!("effect {} {} {} {}", target, effect, seconds, amplifier),
+            EffectCommand::Clear { target } => format!("effect {} clear", target),
+        })
+    }
+}
+
+impl TryFrom<CommandResponsePacket> for EffectCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        Ok(Self {
+            meta: CommandMeta::from(&value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command_line;
+
+    #[test]
+    fn command_lines() {
+        assert_eq!(
+            command_line(EffectCommand::clear_all(TargetSelector::players())),
+            "effect @a clear"
+        );
+        assert_eq!(
+            command_line(EffectCommand::give("Steve", "speed", 30, 1)),
+            "effect Steve speed 30 1"
+        );
+    }
+}
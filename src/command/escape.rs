@@ -0,0 +1,107 @@
+use serde_json::{Map, Value};
+
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value.chars().any(|c| {
+            c.is_whitespace() || matches!(c, ',' | '=' | ':' | '[' | ']' | '{' | '}' | '"' | '\\')
+        })
+}
+
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn quote_if_needed(value: &str) -> String {
+    if needs_quotes(value) {
+        quote(value)
+    } else {
+        value.to_string()
+    }
+}
+
+// For arguments that run to the end of the command line, which can't span lines.
+pub fn single_line(value: &str) -> String {
+    value.lines().collect::<Vec<_>>().join(" ")
+}
+
+pub fn json(value: &Value) -> String {
+    // Serializing a `Value` can't fail, and serde_json already escapes quotes
+    // and control characters inside strings.
+    value.to_string()
+}
+
+fn block_state_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => quote(value),
+        Value::Null => quote(""),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::Array(_) | Value::Object(_) => quote(&value.to_string()),
+    }
+}
+
+pub fn block_states(states: &Map<String, Value>) -> String {
+    let states: Vec<String> = states
+        .iter()
+        .map(|(key, value)| format!("{}={}", quote(key), block_state_value(value)))
+        .collect();
+
+    format!("[{}]", states.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn quotes_only_when_needed() {
+        assert_eq!(quote_if_needed("Steve"), "Steve");
+        assert_eq!(quote_if_needed("Big Steve"), "\"Big Steve\"");
+        assert_eq!(quote_if_needed(""), "\"\"");
+        assert_eq!(quote_if_needed("a,b"), "\"a,b\"");
+    }
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        assert_eq!(quote(r#"say "hi"\"#), r#""say \"hi\"\\""#);
+        assert_eq!(quote("two\nlines"), r#""two\nlines""#);
+    }
+
+    #[test]
+    fn joins_lines() {
+        assert_eq!(single_line("first\nsecond\r\nthird"), "first second third");
+    }
+
+    #[test]
+    fn json_escapes_strings() {
+        let value = json!({ "text": "a \"quoted\" name" });
+        assert_eq!(json(&value), r#"{"text":"a \"quoted\" name"}"#);
+    }
+
+    #[test]
+    fn renders_block_states() {
+        let states = json!({
+            "color": "light blue",
+            "facing_direction": 2,
+            "open_bit": true,
+            "quote": "\"",
+        });
+        assert_eq!(
+            block_states(states.as_object().unwrap()),
+            r#"["color"="light blue","facing_direction"=2,"open_bit"=true,"quote"="\""]"#
+        );
+    }
+}
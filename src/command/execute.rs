@@ -0,0 +1,191 @@
+use super::{escape::quote_if_needed, Command, CommandMeta, TargetSelector};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Renders as the `matches` range syntax: `5`, `1..`, `..10` or `1..10`, all inclusive.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreRange {
+    Exact(i32),
+    Min(i32),
+    Max(i32),
+    Range(i32, i32),
+}
+
+impl fmt::Display for ScoreRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Exact(value) => write!(f, "{}", value),
+            Self::Min(min) => write!(f, "{}..", min),
+            Self::Max(max) => write!(f, "..{}", max),
+            Self::Range(min, max) => write!(f, "{}..{}", min, max),
+        }
+    }
+}
+
+// Builds `execute` one subcommand at a time and finishes with the command to run.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExecuteBuilder {
+    subcommands: Vec<String>,
+}
+
+impl ExecuteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subcommand(mut self, subcommand: &str) -> Self {
+        self.subcommands.push(subcommand.to_string());
+        self
+    }
+
+    pub fn as_<T: Into<TargetSelector>>(self, target: T) -> Self {
+        self.subcommand(&format!("as {}", target.into()))
+    }
+
+    pub fn at<T: Into<TargetSelector>>(self, target: T) -> Self {
+        self.subcommand(&format!("at {}", target.into()))
+    }
+
+    pub fn if_score<T: Into<TargetSelector>>(
+        self,
+        target: T,
+        objective: &str,
+        range: ScoreRange,
+    ) -> Self {
+        self.score_predicate("if", target.into(), objective, range)
+    }
+
+    pub fn unless_score<T: Into<TargetSelector>>(
+        self,
+        target: T,
+        objective: &str,
+        range: ScoreRange,
+    ) -> Self {
+        self.score_predicate("unless", target.into(), objective, range)
+    }
+
+    fn score_predicate(
+        self,
+        condition: &str,
+        target: TargetSelector,
+        objective: &str,
+        range: ScoreRange,
+    ) -> Self {
+        self.subcommand(&format!(
+            "{} score {} {} matches {}",
+            condition,
+            target,
+            quote_if_needed(objective),
+            range
+        ))
+    }
+
+    pub fn run<T: Into<CommandRequestPacket>>(self, command: T) -> ExecuteCommand {
+        ExecuteCommand {
+            subcommands: self.subcommands,
+            command: command.into().command_line,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExecuteCommand {
+    pub subcommands: Vec<String>,
+    pub command: String,
+}
+
+// The inner command's own reply isn't forwarded, so only the status is available.
+pub struct ExecuteCommandResponse {
+    pub meta: CommandMeta,
+}
+
+impl Command for ExecuteCommand {
+    type Response = ExecuteCommandResponse;
+}
+
+impl From<ExecuteCommand> for CommandRequestPacket {
+    fn from(value: ExecuteCommand) -> Self {
+        let mut command_line = "execute".to_string();
+        for subcommand in value.subcommands {
+            command_line.push(' ');
+            command_line.push_str(&subcommand);
+        }
+        command_line.push_str(" run ");
+        command_line.push_str(&value.command);
+
+        Self::new(&command_line)
+    }
+}
+
+impl TryFrom<CommandResponsePacket> for ExecuteCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        Ok(Self {
+            meta: CommandMeta::from(&value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command::SayCommand, test_util::command_line};
+
+    fn if_score(range: ScoreRange) -> String {
+        command_line(
+            ExecuteBuilder::new()
+                .if_score(TargetSelector::executor(), "kills", range)
+                .run(SayCommand::new("hi")),
+        )
+    }
+
+    #[test]
+    fn exact_ranges() {
+        assert_eq!(
+            if_score(ScoreRange::Exact(5)),
+            "execute if score @s kills matches 5 run say hi"
+        );
+    }
+
+    #[test]
+    fn open_ended_ranges() {
+        assert_eq!(
+            if_score(ScoreRange::Min(1)),
+            "execute if score @s kills matches 1.. run say hi"
+        );
+        assert_eq!(
+            if_score(ScoreRange::Max(-3)),
+            "execute if score @s kills matches ..-3 run say hi"
+        );
+    }
+
+    #[test]
+    fn bounded_ranges() {
+        assert_eq!(
+            if_score(ScoreRange::Range(1, 10)),
+            "execute if score @s kills matches 1..10 run say hi"
+        );
+    }
+
+    #[test]
+    fn unless_and_chained_subcommands() {
+        let command = ExecuteBuilder::new()
+            .as_(TargetSelector::players())
+            .at(TargetSelector::executor())
+            .unless_score(
+                TargetSelector::executor(),
+                "team points",
+                ScoreRange::Min(100),
+            )
+            .run(SayCommand::new("keep going"));
+        assert_eq!(
+            command_line(command),
+            r#"execute as @a at @s unless score @s "team points" matches 100.. run say keep going"#
+        );
+    }
+}
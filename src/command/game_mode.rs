@@ -0,0 +1,126 @@
+use super::{Command, CommandMeta, TargetSelector, parse_enum_from_message};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Survival => "survival",
+            Self::Creative => "creative",
+            Self::Adventure => "adventure",
+            Self::Spectator => "spectator",
+        })
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "survival" => Ok(Self::Survival),
+            "creative" => Ok(Self::Creative),
+            "adventure" => Ok(Self::Adventure),
+            "spectator" => Ok(Self::Spectator),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+// Not a query: Bedrock has no read-only form of `/gamemode`, so this always changes the
+// target's game mode. The response only echoes the mode from the confirmation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GameModeCommand {
+    pub game_mode: GameMode,
+    pub target: TargetSelector,
+}
+
+impl GameModeCommand {
+    pub fn new<T: Into<TargetSelector>>(game_mode: GameMode, target: T) -> Self {
+        Self {
+            game_mode,
+            target: target.into(),
+        }
+    }
+}
+
+pub struct GameModeCommandResponse {
+    pub meta: CommandMeta,
+    pub game_mode: GameMode,
+}
+
+impl Command for GameModeCommand {
+    type Response = GameModeCommandResponse;
+}
+
+impl From<GameModeCommand> for CommandRequestPacket {
+    fn from(value: GameModeCommand) -> Self {
+        Self::new(format!("gamemode {} {}", value.game_mode, value.target).as_str())
+    }
+}
+
+// Replies read "Set own game mode to Creative" or "Set Steve's game mode to Survival Mode".
+impl TryFrom<CommandResponsePacket> for GameModeCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+
+        Ok(Self {
+            meta,
+            game_mode: parse_enum_from_message(&message).ok_or(Error::InvalidType)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::status_reply;
+
+    fn parse(message: &str) -> Result<GameModeCommandResponse> {
+        status_reply(0, message).try_into()
+    }
+
+    #[test]
+    fn own_game_mode_is_parsed() {
+        let response = parse("Set own game mode to Creative").unwrap();
+        assert_eq!(response.game_mode, GameMode::Creative);
+    }
+
+    #[test]
+    fn other_players_game_mode_is_parsed() {
+        let response = parse("Set Steve's game mode to Survival Mode").unwrap();
+        assert_eq!(response.game_mode, GameMode::Survival);
+    }
+
+    #[test]
+    fn unknown_game_mode_is_rejected() {
+        assert!(matches!(
+            parse("Set own game mode to Hardcore"),
+            Err(Error::InvalidType)
+        ));
+    }
+
+    #[test]
+    fn request_names_mode_and_target() {
+        let packet = CommandRequestPacket::from(GameModeCommand::new(GameMode::Adventure, "Steve"));
+        assert_eq!(packet.command_line, "gamemode adventure Steve");
+    }
+}
@@ -0,0 +1,133 @@
+use super::{escape::quote_if_needed, Command, CommandMeta};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum GameTestRunCommand {
+    Run { name: String },
+    RunSet { tag: Option<String> },
+}
+
+impl GameTestRunCommand {
+    pub fn run(name: &str) -> Self {
+        Self::Run {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn run_set(tag: Option<&str>) -> Self {
+        Self::RunSet {
+            tag: tag.map(str::to_string),
+        }
+    }
+}
+
+pub struct GameTestRunCommandResponse {
+    pub meta: CommandMeta,
+    pub passed: u32,
+    pub failed: u32,
+}
+
+impl Command for GameTestRunCommand {
+    type Response = GameTestRunCommandResponse;
+}
+
+impl From<GameTestRunCommand> for CommandRequestPacket {
+    fn from(value: GameTestRunCommand) -> Self {
+        Self::new(&match value {
+            GameTestRunCommand::Run { name } => format!("gametest run {}", quote_if_needed(&name)),
+            GameTestRunCommand::RunSet { tag: Some(tag) } => {
+                format!("gametest runset {}", quote_if_needed(&tag))
+            }
+            GameTestRunCommand::RunSet { tag: None } => "gametest runset".to_string(),
+        })
+    }
+}
+
+// Summaries read e.g. "Tests completed: 4 passed, 1 failed." or "All 3 tests passed".
+// Each count is the last number before its keyword; a missing count means none, but a
+// message with neither is not a summary.
+impl TryFrom<CommandResponsePacket> for GameTestRunCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+
+        let mut passed = None;
+        let mut failed = None;
+        let mut last_number = None;
+
+        for word in message.split_whitespace() {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+
+            match word.as_str() {
+                "passed" | "succeeded" => passed = passed.or(last_number.take()),
+                "failed" => failed = failed.or(last_number.take()),
+                _ => {
+                    if let Ok(number) = word.parse::<u32>() {
+                        last_number = Some(number);
+                    }
+                }
+            }
+        }
+
+        if passed.is_none() && failed.is_none() {
+            return Err(Error::InvalidType);
+        }
+
+        Ok(Self {
+            meta,
+            passed: passed.unwrap_or(0),
+            failed: failed.unwrap_or(0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{command_line, status_reply};
+
+    fn parse(message: &str) -> Result<GameTestRunCommandResponse> {
+        status_reply(0, message).try_into()
+    }
+
+    #[test]
+    fn command_lines() {
+        assert_eq!(
+            command_line(GameTestRunCommand::run("redstone:door")),
+            r#"gametest run "redstone:door""#
+        );
+        assert_eq!(
+            command_line(GameTestRunCommand::run_set(Some("smoke"))),
+            "gametest runset smoke"
+        );
+        assert_eq!(
+            command_line(GameTestRunCommand::run_set(None)),
+            "gametest runset"
+        );
+    }
+
+    #[test]
+    fn summaries_give_both_counts() {
+        let response = parse("Tests completed: 4 passed, 1 failed.").unwrap();
+        assert_eq!((response.passed, response.failed), (4, 1));
+    }
+
+    #[test]
+    fn a_missing_count_means_none() {
+        let response = parse("All 3 tests passed").unwrap();
+        assert_eq!((response.passed, response.failed), (3, 0));
+    }
+
+    #[test]
+    fn other_messages_are_not_summaries() {
+        assert!(matches!(parse("Running 5 tests"), Err(Error::InvalidType)));
+    }
+}
@@ -0,0 +1,66 @@
+use super::Coordinates;
+use crate::event::Position;
+
+crate::command! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct GetTopSolidBlockCommand {
+        pub position: Coordinates,
+    }
+    => "gettopsolidblock {}", position;
+    pub struct GetTopSolidBlockCommandResponse {
+        pub block_name: String => "blockName",
+        pub position: Position => "position",
+    }
+}
+
+impl GetTopSolidBlockCommand {
+    pub fn new<T: Into<Coordinates>>(position: T) -> Self {
+        Self {
+            position: position.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{packet::Packet, test_util::command_line};
+
+    #[test]
+    fn command_line_takes_the_column() {
+        let command = GetTopSolidBlockCommand::new(Coordinates::absolute(10.0, 0.0, -4.0));
+        assert_eq!(command_line(command), "gettopsolidblock 10 0 -4");
+    }
+
+    #[test]
+    fn captured_replies_parse() {
+        let json = r#"{
+            "header": {
+                "version": 1,
+                "messagePurpose": "commandResponse",
+                "requestId": "00000000-0000-0000-0000-000000000000"
+            },
+            "body": {
+                "blockName": "Grass Block",
+                "position": { "x": 10, "y": 68, "z": -4 },
+                "statusCode": 0,
+                "statusMessage": "Grass Block (10, 68, -4)"
+            }
+        }"#;
+        let Packet::CommandResponse(response) = Packet::from_json(json).unwrap() else {
+            panic!("expected a command response");
+        };
+
+        let response = GetTopSolidBlockCommandResponse::try_from(response).unwrap();
+        assert_eq!(response.block_name, "Grass Block");
+        assert_eq!(
+            response.position,
+            Position {
+                x: 10.0,
+                y: 68.0,
+                z: -4.0,
+                dimension: None,
+            }
+        );
+    }
+}
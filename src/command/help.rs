@@ -1,56 +1,93 @@
-use std::collections::HashMap;
-
-use super::Command;
+use super::{Command, CommandMeta};
 use crate::{
     packet::{CommandRequestPacket, CommandResponsePacket},
     Error, Result,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BedrockCommandArgument {
     Literal,
     Optional(String),
     Required(String),
+    Enum(Vec<String>),
+    OptionalEnum(Vec<String>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BedrockCommandSchema {
     pub name: String,
-    pub arguments: HashMap<String, BedrockCommandArgument>,
+    // Kept in usage order; literals can repeat, so this isn't keyed by name.
+    pub arguments: Vec<(String, BedrockCommandArgument)>,
+}
+
+// Reads up to the bracket that closes the one already consumed, skipping over any
+// brackets nested inside it.
+fn take_bracketed<I: Iterator<Item = char>>(char_iter: &mut I, end_char: char) -> String {
+    let mut arg = String::new();
+    let mut depth = 0;
+
+    for c in char_iter.by_ref() {
+        match c {
+            '<' | '[' => depth += 1,
+            c if c == end_char && depth == 0 => break,
+            '>' | ']' if depth > 0 => depth -= 1,
+            _ => {}
+        }
+        arg.push(c);
+    }
+
+    arg
 }
 
 fn process_typed_arg<I: Iterator<Item = char>>(
     char_iter: &mut I,
     end_char: char,
     key: &mut String,
-) -> Option<String> {
-    let arg: String = char_iter.take_while(|c| *c != end_char).collect();
-    let (key_str, arg_type) = arg.split_once(": ")?;
+) -> Option<BedrockCommandArgument> {
+    let arg = take_bracketed(char_iter, end_char);
     char_iter.next();
+
+    // Some enums are written without a name, e.g. `<clear|rain|thunder>`.
+    let (key_str, arg_type) = match arg.split_once(": ") {
+        Some(split) => split,
+        None if arg.contains('|') => ("", arg.as_str()),
+        None => return None,
+    };
     key.push_str(key_str);
-    Some(arg_type.to_string())
+
+    let optional = end_char == ']';
+    Some(if arg_type.contains('|') {
+        let options = arg_type
+            .split('|')
+            .map(|option| option.trim().to_string())
+            .collect();
+
+        if optional {
+            BedrockCommandArgument::OptionalEnum(options)
+        } else {
+            BedrockCommandArgument::Enum(options)
+        }
+    } else if optional {
+        BedrockCommandArgument::Optional(arg_type.to_string())
+    } else {
+        BedrockCommandArgument::Required(arg_type.to_string())
+    })
 }
 
 impl BedrockCommandSchema {
     fn from_str(value: &str) -> Option<Self> {
-        let mut arguments = HashMap::new();
-        let (name, args) = value.get(1..)?.split_once(' ')?;
+        let mut arguments = vec![];
+        let value = value.trim_end().strip_prefix('/')?;
+        let (name, args) = value.split_once(' ').unwrap_or((value, ""));
         let mut arg_chars = args.chars();
 
         while let Some(first_char) = arg_chars.next() {
             let mut key = String::new();
 
             let value = match first_char {
-                '<' => BedrockCommandArgument::Required(process_typed_arg(
-                    &mut arg_chars,
-                    '>',
-                    &mut key,
-                )?),
-                '[' => BedrockCommandArgument::Optional(process_typed_arg(
-                    &mut arg_chars,
-                    ']',
-                    &mut key,
-                )?),
+                '<' => process_typed_arg(&mut arg_chars, '>', &mut key)?,
+                '[' => process_typed_arg(&mut arg_chars, ']', &mut key)?,
                 first_char => {
                     key.push(first_char);
                     key.push_str(
@@ -62,7 +99,7 @@ impl BedrockCommandSchema {
                 }
             };
 
-            arguments.insert(key.to_string(), value);
+            arguments.push((key, value));
         }
 
         Some(Self {
@@ -72,11 +109,13 @@ impl BedrockCommandSchema {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct HelpCommand {
     pub page: u32,
 }
 
 pub struct HelpCommandResponse {
+    pub meta: CommandMeta,
     pub body: String,
     pub page: u32,
     pub page_count: u32,
@@ -87,14 +126,21 @@ impl HelpCommandResponse {
         let mut commands = vec![];
 
         for line in self.body.split("\n") {
-            match BedrockCommandSchema::from_str(line) {
-                Some(value) => commands.push(value),
-                None => {}
+            if let Some(value) = BedrockCommandSchema::from_str(line) {
+                commands.push(value);
             }
         }
 
         commands
     }
+
+    // Lines that aren't command usages, such as category headers.
+    pub fn get_other_lines(&self) -> Vec<&str> {
+        self.body
+            .split('\n')
+            .filter(|line| !line.trim().is_empty() && BedrockCommandSchema::from_str(line).is_none())
+            .collect()
+    }
 }
 
 impl Command for HelpCommand {
@@ -117,7 +163,9 @@ impl TryFrom<CommandResponsePacket> for HelpCommandResponse {
     type Error = Error;
 
     fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
         Ok(Self {
+            meta,
             body: value
                 .extra_data
                 .get("body")
@@ -140,3 +188,116 @@ impl TryFrom<CommandResponsePacket> for HelpCommandResponse {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use BedrockCommandArgument::*;
+
+    fn parse(line: &str) -> Vec<(String, BedrockCommandArgument)> {
+        BedrockCommandSchema::from_str(line).unwrap().arguments
+    }
+
+    fn argument(key: &str, value: BedrockCommandArgument) -> (String, BedrockCommandArgument) {
+        (key.to_string(), value)
+    }
+
+    fn options(options: &[&str]) -> Vec<String> {
+        options.iter().map(|option| option.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_order_and_duplicate_names() {
+        let schema = BedrockCommandSchema::from_str(
+            "/scoreboard players operation <targetName: target> <objective: string> \
+             <operation: operator> <selector: target> <objective: string>",
+        )
+        .unwrap();
+
+        assert_eq!(schema.name, "scoreboard");
+        assert_eq!(
+            schema.arguments,
+            vec![
+                argument("players", Literal),
+                argument("operation", Literal),
+                argument("targetName", Required("target".to_string())),
+                argument("objective", Required("string".to_string())),
+                argument("operation", Required("operator".to_string())),
+                argument("selector", Required("target".to_string())),
+                argument("objective", Required("string".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_fill_usage() {
+        assert_eq!(
+            parse(
+                "/fill <from: x y z> <to: x y z> <tileName: Block> [tileData: int] \
+                 [oldBlockHandling: destroy|hollow|keep|outline|replace]"
+            ),
+            vec![
+                argument("from", Required("x y z".to_string())),
+                argument("to", Required("x y z".to_string())),
+                argument("tileName", Required("Block".to_string())),
+                argument("tileData", Optional("int".to_string())),
+                argument(
+                    "oldBlockHandling",
+                    OptionalEnum(options(&[
+                        "destroy", "hollow", "keep", "outline", "replace"
+                    ]))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_gamemode_usage() {
+        assert_eq!(
+            parse("/gamemode <gameMode: survival|creative|adventure|spectator> [player: target]"),
+            vec![
+                argument(
+                    "gameMode",
+                    Enum(options(&["survival", "creative", "adventure", "spectator"]))
+                ),
+                argument("player", Optional("target".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_unnamed_enums() {
+        assert_eq!(
+            parse("/weather <clear|rain|thunder> [duration: int]"),
+            vec![
+                argument("", Enum(options(&["clear", "rain", "thunder"]))),
+                argument("duration", Optional("int".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_nested_brackets() {
+        assert_eq!(
+            parse(
+                "/setblock <position: x y z> <tileName: Block> \
+                 [blockStates: [block states]] replace"
+            ),
+            vec![
+                argument("position", Required("x y z".to_string())),
+                argument("tileName", Required("Block".to_string())),
+                argument("blockStates", Optional("[block states]".to_string())),
+                argument("replace", Literal),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_lines_that_are_not_usages() {
+        assert_eq!(
+            BedrockCommandSchema::from_str("§2--- Showing help page 1 of 9 ---"),
+            None
+        );
+        assert_eq!(parse("/list"), vec![]);
+    }
+}
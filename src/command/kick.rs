@@ -0,0 +1,98 @@
+use super::{escape, Command, CommandMeta, TargetSelector};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KickCommand {
+    pub target: TargetSelector,
+    pub reason: Option<String>,
+}
+
+impl KickCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T) -> Self {
+        Self {
+            target: target.into(),
+            reason: None,
+        }
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+}
+
+pub struct KickCommandResponse {
+    pub meta: CommandMeta,
+    pub target: String,
+}
+
+impl Command for KickCommand {
+    type Response = KickCommandResponse;
+}
+
+impl From<KickCommand> for CommandRequestPacket {
+    fn from(value: KickCommand) -> Self {
+        // The reason is the rest of the line, so it's passed through unquoted.
+        match value.reason {
+            Some(reason) => Self::new(
+                format!("kick {} {}", value.target, escape::single_line(&reason)).as_str(),
+            ),
+            None => Self::new(format!("kick {}", value.target).as_str()),
+        }
+    }
+}
+
+// Replies read "Kicked Steve from the game", followed by ": 'reason'" when one was given.
+impl TryFrom<CommandResponsePacket> for KickCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+        let (target, _) = message
+            .strip_prefix("Kicked ")
+            .and_then(|message| message.split_once(" from the game"))
+            .ok_or(Error::InvalidType)?;
+
+        Ok(Self {
+            meta,
+            target: target.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{command_line, status_reply};
+
+    #[test]
+    fn without_reason() {
+        assert_eq!(command_line(KickCommand::new("Steve")), "kick Steve");
+        assert_eq!(
+            command_line(KickCommand::new("Big Steve")),
+            "kick \"Big Steve\""
+        );
+    }
+
+    #[test]
+    fn with_reason() {
+        let command = KickCommand::new(TargetSelector::players().tag("afk"))
+            .with_reason("idle for\ntoo long");
+        assert_eq!(command_line(command), "kick @a[tag=afk] idle for too long");
+    }
+
+    #[test]
+    fn parses_kicked_player() {
+        let response =
+            KickCommandResponse::try_from(status_reply(0, "Kicked Steve from the game: 'idle'"))
+                .unwrap();
+        assert_eq!(response.target, "Steve");
+    }
+}
@@ -0,0 +1,35 @@
+use super::TargetSelector;
+use serde::{Deserialize, Serialize};
+
+crate::command! {
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct KillCommand {
+        pub target: TargetSelector,
+    }
+    => "kill {}", target;
+    pub struct KillCommandResponse {}
+}
+
+impl KillCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T) -> Self {
+        Self {
+            target: target.into(),
+        }
+    }
+
+    pub fn all_entities() -> Self {
+        Self::new(TargetSelector::all_entities())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command_line;
+
+    #[test]
+    fn command_lines() {
+        assert_eq!(command_line(KillCommand::all_entities()), "kill @e");
+        assert_eq!(command_line(KillCommand::new("Steve")), "kill Steve");
+    }
+}
@@ -0,0 +1,142 @@
+// Generates a command struct, its response struct and the conversions between them
+// and the packet types. The command line is built with `format!` from the listed
+// fields, and each response field is read from the response body under its key. The
+// response also gets a `meta` field holding the status code and message.
+//
+// command! {
+//     pub struct SayCommand { pub message: String }
+//     => "say {}", message;
+//     pub struct SayCommandResponse { pub message: String => "message" }
+// }
+#[macro_export]
+macro_rules! command {
+    (
+        $(#[$command_meta:meta])*
+        $command_vis:vis struct $command:ident;
+        => $format:literal $(, $arg:ident)* $(,)?;
+        $($response:tt)*
+    ) => {
+        $(#[$command_meta])*
+        $command_vis struct $command;
+
+        $crate::command!(@impl $command => $format $(, $arg)*; $($response)*);
+    };
+    (
+        $(#[$command_meta:meta])*
+        $command_vis:vis struct $command:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $field_type:ty),* $(,)?
+        }
+        => $format:literal $(, $arg:ident)* $(,)?;
+        $($response:tt)*
+    ) => {
+        $(#[$command_meta])*
+        $command_vis struct $command {
+            $($(#[$field_meta])* $field_vis $field: $field_type),*
+        }
+
+        $crate::command!(@impl $command => $format $(, $arg)*; $($response)*);
+    };
+    (
+        @impl $command:ident => $format:literal $(, $arg:ident)*;
+        $(#[$response_meta:meta])*
+        $response_vis:vis struct $response:ident {
+            $($(#[$key_meta:meta])* $key_vis:vis $key_field:ident: $key_type:ty => $key:literal),* $(,)?
+        }
+    ) => {
+        $(#[$response_meta])*
+        $response_vis struct $response {
+            pub meta: $crate::command::CommandMeta,
+            $($(#[$key_meta])* $key_vis $key_field: $key_type),*
+        }
+
+        impl $crate::command::Command for $command {
+            type Response = $response;
+        }
+
+        impl ::std::convert::From<$command> for $crate::packet::CommandRequestPacket {
+            #[allow(unused_variables)]
+            fn from(value: $command) -> Self {
+                Self::new(&format!($format $(, value.$arg)*))
+            }
+        }
+
+        impl ::std::convert::TryFrom<$crate::packet::CommandResponsePacket> for $response {
+            type Error = $crate::Error;
+
+            #[allow(unused_variables)]
+            fn try_from(value: $crate::packet::CommandResponsePacket) -> $crate::Result<Self> {
+                Ok(Self {
+                    meta: $crate::command::CommandMeta::from(&value),
+                    $($key_field: value.get_field($key)?),*
+                })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{packet::CommandResponsePacket, test_util::command_line, Error};
+    use serde_json::json;
+
+    crate::command! {
+        #[derive(Debug)]
+        pub struct GreetCommand {
+            pub name: String,
+            pub times: u32,
+        }
+        => "greet {} {}", name, times;
+        #[derive(Debug)]
+        pub struct GreetCommandResponse {
+            pub greeted: String => "greeted",
+            pub count: u32 => "count",
+        }
+    }
+
+    fn reply(body: serde_json::Value) -> CommandResponsePacket {
+        let serde_json::Value::Object(extra_data) = body else {
+            unreachable!()
+        };
+        CommandResponsePacket {
+            status_message: Some("Greeted".to_string()),
+            extra_data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn the_command_line_follows_the_format() {
+        let command = GreetCommand {
+            name: "Steve".to_string(),
+            times: 3,
+        };
+        assert_eq!(command_line(command), "greet Steve 3");
+    }
+
+    #[test]
+    fn response_fields_are_read_by_key() {
+        let response =
+            GreetCommandResponse::try_from(reply(json!({ "greeted": "Steve", "count": 3 })))
+                .unwrap();
+        assert_eq!(response.greeted, "Steve");
+        assert_eq!(response.count, 3);
+        assert_eq!(response.meta.status_message.as_deref(), Some("Greeted"));
+    }
+
+    #[test]
+    fn missing_keys_are_reported() {
+        let response = GreetCommandResponse::try_from(reply(json!({ "greeted": "Steve" })));
+        assert!(matches!(response, Err(Error::MissingField("count"))));
+    }
+
+    #[test]
+    fn unit_commands_take_a_bare_line() {
+        crate::command! {
+            pub struct PingCommand;
+            => "ping";
+            pub struct PingCommandResponse {}
+        }
+
+        assert_eq!(command_line(PingCommand), "ping");
+    }
+}
@@ -1,19 +1,92 @@
+mod clear;
+mod coordinates;
+mod dialogue;
+mod difficulty;
+mod effect;
+pub mod escape;
+mod execute;
+mod game_mode;
+mod gametest;
+mod get_top_solid_block;
 pub mod help;
+mod kick;
+mod kill;
 mod local_player_name;
+mod macros;
+mod op;
+mod query_target;
 mod say;
+mod selector;
+mod set_max_players;
+mod tell;
+mod tellraw;
+mod tick;
+mod weather;
 
+pub use clear::*;
+pub use coordinates::{Coordinate, Coordinates};
+pub use dialogue::*;
+pub use difficulty::*;
+pub use effect::*;
+pub use execute::*;
+pub use game_mode::*;
+pub use gametest::*;
+pub use get_top_solid_block::*;
 pub use help::{HelpCommand, HelpCommandResponse};
+pub use kick::*;
+pub use kill::*;
 pub use local_player_name::*;
+pub use op::*;
+pub use query_target::*;
 pub use say::*;
+pub use selector::TargetSelector;
+pub use set_max_players::*;
+pub use tell::*;
+pub use tellraw::*;
+pub use tick::*;
+pub use weather::*;
 
 use crate::{
     packet::{CommandRequestPacket, CommandResponsePacket},
     Error,
 };
+use std::str::FromStr;
+
+// The status every typed response carries alongside its parsed fields, so warnings in
+// otherwise successful replies aren't lost.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommandMeta {
+    pub status_code: i32,
+    pub status_message: Option<String>,
+}
+
+impl From<&CommandResponsePacket> for CommandMeta {
+    fn from(value: &CommandResponsePacket) -> Self {
+        Self {
+            status_code: value.status_code,
+            status_message: value.status_message.clone(),
+        }
+    }
+}
 
 pub trait Command: Into<CommandRequestPacket>
 where
     Self::Response: TryFrom<CommandResponsePacket, Error = Error>,
 {
     type Response;
+
+    // Failure replies that still describe the outcome, such as `/op` on an existing
+    // operator, are parsed into the response instead of becoming `Error::MinecraftError`.
+    fn is_expected_failure(_response: &CommandResponsePacket) -> bool {
+        false
+    }
+}
+
+// Game state comes back as prose such as "Weather state is: clear". The last word that
+// parses is taken, so a player name earlier in the message can't be mistaken for it.
+pub fn parse_enum_from_message<T: FromStr>(message: &str) -> Option<T> {
+    message
+        .split_whitespace()
+        .rev()
+        .find_map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).parse().ok())
 }
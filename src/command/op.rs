@@ -0,0 +1,182 @@
+use super::{Command, CommandMeta, TargetSelector};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OpCommand {
+    pub target: TargetSelector,
+}
+
+impl OpCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T) -> Self {
+        Self {
+            target: target.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeopCommand {
+    pub target: TargetSelector,
+}
+
+impl DeopCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T) -> Self {
+        Self {
+            target: target.into(),
+        }
+    }
+}
+
+// `changed` is false when the player already had (or lacked) operator status. The game
+// reports that with a failure status, which `send_command` still parses rather than
+// returning as an error.
+pub struct OpCommandResponse {
+    pub meta: CommandMeta,
+    pub target: String,
+    pub changed: bool,
+}
+
+pub struct DeopCommandResponse {
+    pub meta: CommandMeta,
+    pub target: String,
+    pub changed: bool,
+}
+
+impl Command for OpCommand {
+    type Response = OpCommandResponse;
+
+    fn is_expected_failure(response: &CommandResponsePacket) -> bool {
+        is_unchanged(response)
+    }
+}
+
+impl Command for DeopCommand {
+    type Response = DeopCommandResponse;
+
+    fn is_expected_failure(response: &CommandResponsePacket) -> bool {
+        is_unchanged(response)
+    }
+}
+
+impl From<OpCommand> for CommandRequestPacket {
+    fn from(value: OpCommand) -> Self {
+        Self::new(format!("op {}", value.target).as_str())
+    }
+}
+
+impl From<DeopCommand> for CommandRequestPacket {
+    fn from(value: DeopCommand) -> Self {
+        Self::new(format!("deop {}", value.target).as_str())
+    }
+}
+
+// Replies read "Opped: Steve" or "Could not op (already op or higher): Steve", and
+// "De-opped: Steve" or "Could not de-op (permission level too high or not op): Steve".
+fn parse_permission_change(message: &str) -> Result<(String, bool)> {
+    let (status, target) = message.rsplit_once(": ").ok_or(Error::InvalidType)?;
+    Ok((target.to_string(), !status.starts_with("Could not")))
+}
+
+fn is_unchanged(response: &CommandResponsePacket) -> bool {
+    response
+        .status_message
+        .as_deref()
+        .is_some_and(|message| message.starts_with("Could not"))
+}
+
+impl TryFrom<CommandResponsePacket> for OpCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+        let (target, changed) = parse_permission_change(&message)?;
+
+        Ok(Self {
+            meta,
+            target,
+            changed,
+        })
+    }
+}
+
+impl TryFrom<CommandResponsePacket> for DeopCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+        let (target, changed) = parse_permission_change(&message)?;
+
+        Ok(Self {
+            meta,
+            target,
+            changed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{command_line, status_reply};
+
+    #[test]
+    fn command_lines() {
+        assert_eq!(command_line(OpCommand::new("Steve")), "op Steve");
+        assert_eq!(
+            command_line(DeopCommand::new("Big Steve")),
+            "deop \"Big Steve\""
+        );
+    }
+
+    #[test]
+    fn op_changes_permission() {
+        let response = OpCommandResponse::try_from(status_reply(0, "Opped: Steve")).unwrap();
+        assert_eq!(response.target, "Steve");
+        assert!(response.changed);
+    }
+
+    #[test]
+    fn already_op_is_unchanged() {
+        let response = status_reply(-2147483648, "Could not op (already op or higher): Steve");
+        assert!(OpCommand::is_expected_failure(&response));
+
+        let response = OpCommandResponse::try_from(response).unwrap();
+        assert_eq!(response.target, "Steve");
+        assert!(!response.changed);
+    }
+
+    #[test]
+    fn deop_changes_permission() {
+        let response = DeopCommandResponse::try_from(status_reply(0, "De-opped: Steve")).unwrap();
+        assert_eq!(response.target, "Steve");
+        assert!(response.changed);
+    }
+
+    #[test]
+    fn not_op_is_unchanged() {
+        let response = status_reply(
+            -2147483648,
+            "Could not de-op (permission level too high or not op): Steve",
+        );
+        assert!(DeopCommand::is_expected_failure(&response));
+        assert!(!DeopCommandResponse::try_from(response).unwrap().changed);
+    }
+
+    #[test]
+    fn other_failures_are_errors() {
+        assert!(!OpCommand::is_expected_failure(&status_reply(
+            -2147483648,
+            "No targets matched selector"
+        )));
+    }
+}
@@ -0,0 +1,120 @@
+use super::{Command, CommandMeta, TargetSelector};
+use crate::{
+    event::Position,
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QueryTargetCommand {
+    pub target: TargetSelector,
+}
+
+impl QueryTargetCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T) -> Self {
+        Self {
+            target: target.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueriedTarget {
+    pub unique_id: String,
+    pub position: Position,
+    #[serde(default)]
+    pub y_rot: f64,
+}
+
+pub struct QueryTargetCommandResponse {
+    pub meta: CommandMeta,
+    pub targets: Vec<QueriedTarget>,
+}
+
+impl Command for QueryTargetCommand {
+    type Response = QueryTargetCommandResponse;
+}
+
+impl From<QueryTargetCommand> for CommandRequestPacket {
+    fn from(value: QueryTargetCommand) -> Self {
+        Self::new(format!("querytarget {}", value.target).as_str())
+    }
+}
+
+// `details` is a JSON array encoded as a string, with each target's dimension
+// stored beside its position rather than inside it.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTarget {
+    unique_id: String,
+    position: Position,
+    #[serde(default)]
+    dimension: Option<i32>,
+    #[serde(default)]
+    y_rot: f64,
+}
+
+impl TryFrom<CommandResponsePacket> for QueryTargetCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let details: String = value.get_field("details")?;
+        let targets: Vec<RawTarget> = serde_json::from_str(&details)?;
+
+        Ok(Self {
+            meta,
+            targets: targets
+                .into_iter()
+                .map(|target| QueriedTarget {
+                    unique_id: target.unique_id,
+                    position: Position {
+                        dimension: target.dimension.or(target.position.dimension),
+                        ..target.position
+                    },
+                    y_rot: target.y_rot,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::command_line;
+
+    #[test]
+    fn targets_are_queried_by_selector() {
+        assert_eq!(
+            command_line(QueryTargetCommand::new("Steve")),
+            "querytarget Steve"
+        );
+    }
+
+    #[test]
+    fn targets_pick_up_their_dimension() {
+        let details = r#"[{"uniqueId":"-12884901887","position":{"x":1.5,"y":64.0,"z":-2.5},"dimension":1,"yRot":90.0}]"#;
+        let mut response = CommandResponsePacket::default();
+        response
+            .extra_data
+            .insert("details".to_string(), details.into());
+
+        let response = QueryTargetCommandResponse::try_from(response).unwrap();
+        assert_eq!(
+            response.targets,
+            [QueriedTarget {
+                unique_id: "-12884901887".to_string(),
+                position: Position {
+                    x: 1.5,
+                    y: 64.0,
+                    z: -2.5,
+                    dimension: Some(1),
+                },
+                y_rot: 90.0,
+            }]
+        );
+    }
+}
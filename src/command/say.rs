@@ -1,9 +1,11 @@
-use super::Command;
+use super::{escape, ChatColor, Command, CommandMeta, TargetSelector, TellrawCommand};
 use crate::{
     packet::{CommandRequestPacket, CommandResponsePacket},
     Error, Result,
 };
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SayCommand {
     pub message: String,
 }
@@ -14,10 +16,22 @@ impl SayCommand {
             message: message.to_string(),
         }
     }
+
+    // `say` always broadcasts, so targeting a message sends it with `tellraw` instead.
+    pub fn with_target<T: Into<TargetSelector>>(self, target: T) -> TellrawCommand {
+        TellrawCommand::new(target, &self.message)
+    }
+
+    pub fn with_color(self, color: ChatColor) -> TellrawCommand {
+        TellrawCommand::new(TargetSelector::players(), &self.message).with_color(color)
+    }
 }
 
+// Some game versions answer `say` with only a status, so `message` falls back to the
+// status message and is None when neither is present.
 pub struct SayCommandResponse {
-    pub message: String,
+    pub meta: CommandMeta,
+    pub message: Option<String>,
 }
 
 impl Command for SayCommand {
@@ -26,7 +40,7 @@ impl Command for SayCommand {
 
 impl From<SayCommand> for CommandRequestPacket {
     fn from(value: SayCommand) -> Self {
-        Self::new(format!("say {}", value.message).as_str())
+        Self::new(format!("say {}", escape::single_line(&value.message)).as_str())
     }
 }
 
@@ -34,14 +48,101 @@ impl TryFrom<CommandResponsePacket> for SayCommandResponse {
     type Error = Error;
 
     fn try_from(value: CommandResponsePacket) -> Result<Self> {
-        Ok(Self {
-            message: value
-                .extra_data
-                .get("message")
-                .ok_or(Error::MissingField("message"))?
-                .as_str()
-                .ok_or(Error::InvalidType)?
-                .to_string(),
-        })
+        let meta = CommandMeta::from(&value);
+        let message = match value.extra_data.get("message") {
+            Some(message) => Some(message.as_str().ok_or(Error::InvalidType)?.to_string()),
+            None => value.status_message,
+        };
+
+        Ok(Self { meta, message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command::QueryTargetCommand,
+        test_util::{command_line, status_reply},
+    };
+
+    #[test]
+    fn plain_messages_are_said() {
+        assert_eq!(command_line(SayCommand::new("hello all")), "say hello all");
+    }
+
+    #[test]
+    fn targeted_messages_use_tellraw() {
+        assert_eq!(
+            command_line(SayCommand::new("psst").with_target("Steve")),
+            r#"tellraw Steve {"rawtext":[{"text":"psst"}]}"#
+        );
+        assert_eq!(
+            command_line(SayCommand::new("alert").with_color(ChatColor::Red)),
+            r#"tellraw @a {"rawtext":[{"text":"§calert"}]}"#
+        );
+    }
+
+    #[test]
+    fn special_characters_survive() {
+        // `say` can't span lines, so newlines become spaces.
+        assert_eq!(command_line(SayCommand::new("one\ntwo")), "say one two");
+        assert_eq!(
+            command_line(SayCommand::new("§lbold \"quote\"\nnext").with_target("Steve")),
+            r#"tellraw Steve {"rawtext":[{"text":"§lbold \"quote\"\nnext"}]}"#
+        );
+    }
+
+    #[test]
+    fn scripts_round_trip_through_json() {
+        let script = vec![
+            SayCommand::new("hello"),
+            SayCommand::new("a \"quoted\" line"),
+        ];
+        let json = serde_json::to_string(&script).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<SayCommand>>(&json).unwrap(),
+            script
+        );
+    }
+
+    #[test]
+    fn targets_round_trip_through_json() {
+        let commands = [
+            QueryTargetCommand::new("Big Steve"),
+            QueryTargetCommand::new(TargetSelector::raw("@a[")),
+            QueryTargetCommand::new(TargetSelector::nearest().tag("red")),
+        ];
+
+        for command in commands {
+            let json = serde_json::to_string(&command).unwrap();
+            assert_eq!(
+                serde_json::from_str::<QueryTargetCommand>(&json).unwrap(),
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn replies_with_and_without_a_message() {
+        let mut reply = status_reply(0, "Message sent");
+        reply
+            .extra_data
+            .insert("message".to_string(), "hello all".into());
+        let response = SayCommandResponse::try_from(reply).unwrap();
+        assert_eq!(response.message.as_deref(), Some("hello all"));
+
+        let response = SayCommandResponse::try_from(status_reply(0, "Message sent")).unwrap();
+        assert_eq!(response.message.as_deref(), Some("Message sent"));
+
+        let response = SayCommandResponse::try_from(CommandResponsePacket::default()).unwrap();
+        assert_eq!(response.message, None);
+
+        let mut reply = status_reply(0, "Message sent");
+        reply.extra_data.insert("message".to_string(), 7.into());
+        assert!(matches!(
+            SayCommandResponse::try_from(reply),
+            Err(Error::InvalidType)
+        ));
     }
 }
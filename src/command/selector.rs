@@ -0,0 +1,323 @@
+use super::escape::quote_if_needed;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Serialized as the rendered selector string, e.g. `@a[tag=red]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(into = "String", from = "String")]
+pub struct TargetSelector {
+    base: String,
+    arguments: Vec<(String, String)>,
+}
+
+impl TargetSelector {
+    fn variable(variable: &str) -> Self {
+        Self {
+            base: variable.to_string(),
+            arguments: vec![],
+        }
+    }
+
+    // Taken without quoting. A selector with arguments is split back into its parts, so
+    // it compares equal to the same selector built up and can take further arguments.
+    pub fn raw(target: &str) -> Self {
+        if target.starts_with('@') {
+            if let Some(selector) = Self::parse_selector(target) {
+                return selector;
+            }
+        }
+
+        Self {
+            base: target.to_string(),
+            arguments: vec![],
+        }
+    }
+
+    fn parse_selector(target: &str) -> Option<Self> {
+        let (base, arguments) = target.strip_suffix(']')?.split_once('[')?;
+
+        Some(Self {
+            base: base.to_string(),
+            arguments: parse_arguments(arguments)?,
+        })
+    }
+
+    pub fn player(name: &str) -> Self {
+        Self {
+            base: quote_if_needed(name),
+            arguments: vec![],
+        }
+    }
+
+    pub fn players() -> Self {
+        Self::variable("@a")
+    }
+
+    pub fn all_entities() -> Self {
+        Self::variable("@e")
+    }
+
+    pub fn nearest() -> Self {
+        Self::variable("@p")
+    }
+
+    pub fn random() -> Self {
+        Self::variable("@r")
+    }
+
+    pub fn executor() -> Self {
+        Self::variable("@s")
+    }
+
+    pub fn argument(mut self, key: &str, value: &str) -> Self {
+        self.arguments
+            .push((key.to_string(), quote_if_needed(value)));
+        self
+    }
+
+    pub fn type_(self, entity_type: &str) -> Self {
+        self.argument("type", entity_type)
+    }
+
+    pub fn family(self, family: &str) -> Self {
+        self.argument("family", family)
+    }
+
+    pub fn radius(self, radius: f64) -> Self {
+        self.argument("r", &radius.to_string())
+    }
+
+    pub fn min_radius(self, radius: f64) -> Self {
+        self.argument("rm", &radius.to_string())
+    }
+
+    pub fn position(self, x: f64, y: f64, z: f64) -> Self {
+        self.argument("x", &x.to_string())
+            .argument("y", &y.to_string())
+            .argument("z", &z.to_string())
+    }
+
+    pub fn count(self, count: i32) -> Self {
+        self.argument("c", &count.to_string())
+    }
+
+    pub fn tag(self, tag: &str) -> Self {
+        self.argument("tag", tag)
+    }
+
+    pub fn name(self, name: &str) -> Self {
+        self.argument("name", name)
+    }
+
+    pub fn game_mode(self, game_mode: &str) -> Self {
+        self.argument("m", game_mode)
+    }
+
+    pub fn level(self, min: u32, max: u32) -> Self {
+        self.argument("lm", &min.to_string())
+            .argument("l", &max.to_string())
+    }
+}
+
+// Commas only separate arguments outside quoted values and the braces of nested ones
+// like `scores={a=1,b=2}`. Values keep the quoting they were rendered with.
+fn parse_arguments(arguments: &str) -> Option<Vec<(String, String)>> {
+    let mut parsed = vec![];
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, c) in arguments.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match c {
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '{' | '[' if !quoted => depth += 1,
+            '}' | ']' if !quoted => depth = depth.checked_sub(1)?,
+            ',' if !quoted && depth == 0 => {
+                parsed.push(split_argument(&arguments[start..index])?);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if quoted || depth != 0 {
+        return None;
+    }
+    parsed.push(split_argument(&arguments[start..])?);
+    Some(parsed)
+}
+
+fn split_argument(argument: &str) -> Option<(String, String)> {
+    let (key, value) = argument.split_once('=')?;
+    Some((key.trim().to_string(), value.trim().to_string()))
+}
+
+impl fmt::Display for TargetSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.base)?;
+
+        if !self.arguments.is_empty() {
+            f.write_str("[")?;
+            for (index, (key, value)) in self.arguments.iter().enumerate() {
+                if index != 0 {
+                    f.write_str(",")?;
+                }
+                write!(f, "{}={}", key, value)?;
+            }
+            f.write_str("]")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&str> for TargetSelector {
+    fn from(value: &str) -> Self {
+        // Already-quoted names are taken as-is so rendered selectors parse back unchanged.
+        if value.starts_with('@') || value.starts_with('"') {
+            Self::raw(value)
+        } else {
+            Self::player(value)
+        }
+    }
+}
+
+impl From<String> for TargetSelector {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<TargetSelector> for String {
+    fn from(value: TargetSelector) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_variables() {
+        assert_eq!(TargetSelector::players().to_string(), "@a");
+        assert_eq!(TargetSelector::all_entities().to_string(), "@e");
+        assert_eq!(TargetSelector::nearest().to_string(), "@p");
+        assert_eq!(TargetSelector::random().to_string(), "@r");
+        assert_eq!(TargetSelector::executor().to_string(), "@s");
+    }
+
+    #[test]
+    fn renders_arguments_in_order() {
+        let selector = TargetSelector::all_entities()
+            .type_("zombie")
+            .radius(10.0)
+            .count(5);
+        assert_eq!(selector.to_string(), "@e[type=zombie,r=10,c=5]");
+
+        let selector = TargetSelector::players()
+            .position(0.5, 64.0, -3.0)
+            .level(1, 30);
+        assert_eq!(selector.to_string(), "@a[x=0.5,y=64,z=-3,lm=1,l=30]");
+    }
+
+    #[test]
+    fn quotes_argument_values() {
+        let selector = TargetSelector::all_entities().name("Big, \"Bad\" Wolf");
+        assert_eq!(selector.to_string(), r#"@e[name="Big, \"Bad\" Wolf"]"#);
+        assert_eq!(
+            TargetSelector::players().tag("a=b").to_string(),
+            r#"@a[tag="a=b"]"#
+        );
+    }
+
+    #[test]
+    fn raw_strings_still_work() {
+        assert_eq!(TargetSelector::from("Steve").to_string(), "Steve");
+        assert_eq!(
+            TargetSelector::from("Big Steve").to_string(),
+            "\"Big Steve\""
+        );
+        assert_eq!(
+            TargetSelector::from("@a[tag=red]").to_string(),
+            "@a[tag=red]"
+        );
+        assert_eq!(
+            TargetSelector::from("\"Big Steve\"").to_string(),
+            "\"Big Steve\""
+        );
+    }
+
+    #[test]
+    fn serializes_as_rendered_string() {
+        let selector = TargetSelector::players().tag("red");
+        let json = serde_json::to_string(&selector).unwrap();
+        assert_eq!(json, r#""@a[tag=red]""#);
+
+        let selector: TargetSelector = serde_json::from_str(&json).unwrap();
+        assert_eq!(selector.to_string(), "@a[tag=red]");
+    }
+
+    #[test]
+    fn rendered_selectors_parse_back_equal() {
+        let selectors = [
+            TargetSelector::all_entities().type_("zombie"),
+            TargetSelector::all_entities()
+                .name("Big, \"Bad\" Wolf")
+                .count(1),
+        ];
+
+        for selector in selectors {
+            let json = serde_json::to_string(&selector).unwrap();
+            assert_eq!(
+                serde_json::from_str::<TargetSelector>(&json).unwrap(),
+                selector
+            );
+        }
+    }
+
+    #[test]
+    fn parsed_selectors_take_more_arguments() {
+        let selector = TargetSelector::from("@a[tag=red]").count(1);
+        assert_eq!(selector.to_string(), "@a[tag=red,c=1]");
+    }
+
+    #[test]
+    fn nested_arguments_stay_whole() {
+        let selector = TargetSelector::from("@a[scores={kills=1..,deaths=0},tag=red]");
+        assert_eq!(
+            selector.arguments,
+            [
+                ("scores".to_string(), "{kills=1..,deaths=0}".to_string()),
+                ("tag".to_string(), "red".to_string()),
+            ]
+        );
+        assert_eq!(
+            selector.to_string(),
+            "@a[scores={kills=1..,deaths=0},tag=red]"
+        );
+    }
+
+    #[test]
+    fn quoted_names_are_kept_whole() {
+        let selector = TargetSelector::player("Big Steve");
+        assert_eq!(TargetSelector::from("\"Big Steve\""), selector);
+        assert_eq!(TargetSelector::from("Big Steve"), selector);
+    }
+
+    #[test]
+    fn unparseable_selectors_stay_raw() {
+        for target in ["@a[", "@a[]", "@a[tag]", "@e[name=\"open]"] {
+            let selector = TargetSelector::raw(target);
+            assert!(selector.arguments.is_empty());
+            assert_eq!(selector.to_string(), target);
+        }
+    }
+}
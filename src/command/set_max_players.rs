@@ -0,0 +1,86 @@
+use super::{parse_enum_from_message, Command, CommandMeta};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetMaxPlayersCommand {
+    pub max: u32,
+}
+
+impl SetMaxPlayersCommand {
+    pub fn new(max: u32) -> Self {
+        Self { max }
+    }
+}
+
+pub struct SetMaxPlayersCommandResponse {
+    pub meta: CommandMeta,
+    pub max: u32,
+}
+
+impl Command for SetMaxPlayersCommand {
+    type Response = SetMaxPlayersCommandResponse;
+}
+
+impl From<SetMaxPlayersCommand> for CommandRequestPacket {
+    fn from(value: SetMaxPlayersCommand) -> Self {
+        Self::new(format!("setmaxplayers {}", value.max).as_str())
+    }
+}
+
+// Replies read "Set max players to 10.", with "(bound to 30)" appended when the game
+// clamps the request, so the last number is the one applied.
+impl TryFrom<CommandResponsePacket> for SetMaxPlayersCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+
+        Ok(Self {
+            meta,
+            max: parse_enum_from_message(&message).ok_or(Error::InvalidType)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{command_line, status_reply};
+
+    fn parse(message: &str) -> Result<SetMaxPlayersCommandResponse> {
+        status_reply(0, message).try_into()
+    }
+
+    #[test]
+    fn command_line_takes_the_maximum() {
+        assert_eq!(
+            command_line(SetMaxPlayersCommand::new(10)),
+            "setmaxplayers 10"
+        );
+    }
+
+    #[test]
+    fn replies_give_the_applied_maximum() {
+        assert_eq!(parse("Set max players to 10.").unwrap().max, 10);
+    }
+
+    #[test]
+    fn clamped_replies_give_the_bound() {
+        assert_eq!(
+            parse("Set max players to 50. (bound to 30)").unwrap().max,
+            30
+        );
+    }
+
+    #[test]
+    fn replies_without_a_number_are_rejected() {
+        assert!(matches!(parse("Set max players"), Err(Error::InvalidType)));
+    }
+}
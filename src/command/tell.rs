@@ -0,0 +1,100 @@
+use super::{escape, Command, CommandMeta, TargetSelector};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TellCommand {
+    pub target: TargetSelector,
+    pub message: String,
+}
+
+impl TellCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T, message: &str) -> Self {
+        Self {
+            target: target.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+// `recipient` is None when the reply doesn't have the usual wording.
+pub struct TellCommandResponse {
+    pub meta: CommandMeta,
+    pub recipient: Option<String>,
+}
+
+impl Command for TellCommand {
+    type Response = TellCommandResponse;
+}
+
+impl From<TellCommand> for CommandRequestPacket {
+    fn from(value: TellCommand) -> Self {
+        Self::new(
+            format!(
+                "tell {} {}",
+                value.target,
+                escape::single_line(&value.message)
+            )
+            .as_str(),
+        )
+    }
+}
+
+// Replies read "You whisper to Steve: hello"; player names can't contain a colon.
+impl TryFrom<CommandResponsePacket> for TellCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let recipient = value
+            .status_message
+            .as_deref()
+            .and_then(|message| message.strip_prefix("You whisper to "))
+            .and_then(|message| message.split_once(": "))
+            .map(|(recipient, _)| recipient.to_string());
+
+        Ok(Self {
+            meta: CommandMeta::from(&value),
+            recipient,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{command_line, status_reply};
+
+    #[test]
+    fn targets_players_and_selectors() {
+        assert_eq!(
+            command_line(TellCommand::new("Steve", "hi")),
+            "tell Steve hi"
+        );
+        assert_eq!(
+            command_line(TellCommand::new("Big Steve", "hi")),
+            "tell \"Big Steve\" hi"
+        );
+        assert_eq!(
+            command_line(TellCommand::new(TargetSelector::players().tag("red"), "hi")),
+            "tell @a[tag=red] hi"
+        );
+    }
+
+    #[test]
+    fn message_stays_on_one_line() {
+        assert_eq!(
+            command_line(TellCommand::new("Steve", "first\nsecond")),
+            "tell Steve first second"
+        );
+    }
+
+    #[test]
+    fn parses_recipient() {
+        let response =
+            TellCommandResponse::try_from(status_reply(0, "You whisper to Steve: a: b")).unwrap();
+        assert_eq!(response.recipient.as_deref(), Some("Steve"));
+    }
+}
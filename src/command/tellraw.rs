@@ -0,0 +1,105 @@
+use super::{escape, Command, CommandMeta, TargetSelector};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+}
+
+impl ChatColor {
+    // The section-sign formatting code, e.g. "§c" for red.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Black => "§0",
+            Self::DarkBlue => "§1",
+            Self::DarkGreen => "§2",
+            Self::DarkAqua => "§3",
+            Self::DarkRed => "§4",
+            Self::DarkPurple => "§5",
+            Self::Gold => "§6",
+            Self::Gray => "§7",
+            Self::DarkGray => "§8",
+            Self::Blue => "§9",
+            Self::Green => "§a",
+            Self::Aqua => "§b",
+            Self::Red => "§c",
+            Self::LightPurple => "§d",
+            Self::Yellow => "§e",
+            Self::White => "§f",
+        }
+    }
+}
+
+// Unlike `say`, the message is shown without the sender's name. It travels as JSON, so
+// newlines and section signs in it arrive intact.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TellrawCommand {
+    pub target: TargetSelector,
+    pub message: String,
+    pub color: Option<ChatColor>,
+}
+
+impl TellrawCommand {
+    pub fn new<T: Into<TargetSelector>>(target: T, message: &str) -> Self {
+        Self {
+            target: target.into(),
+            message: message.to_string(),
+            color: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: ChatColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+pub struct TellrawCommandResponse {
+    pub meta: CommandMeta,
+}
+
+impl Command for TellrawCommand {
+    type Response = TellrawCommandResponse;
+}
+
+impl From<TellrawCommand> for CommandRequestPacket {
+    fn from(value: TellrawCommand) -> Self {
+        let text = match value.color {
+            Some(color) => format!("{}{}", color.code(), value.message),
+            None => value.message,
+        };
+        let rawtext = json!({ "rawtext": [{ "text": text }] });
+
+        Self::new(format!("tellraw {} {}", value.target, escape::json(&rawtext)).as_str())
+    }
+}
+
+impl TryFrom<CommandResponsePacket> for TellrawCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        Ok(Self {
+            meta: CommandMeta::from(&value),
+        })
+    }
+}
@@ -0,0 +1,104 @@
+use super::{Command, CommandMeta, parse_enum_from_message};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TickCommand {
+    Query,
+    Rate { rate: f32 },
+    Freeze,
+    Unfreeze,
+    Step { ticks: u32 },
+    Sprint { ticks: u32 },
+    SprintStop,
+}
+
+// Only `Query` and `Rate` replies mention a rate, so `rate` is None for the others even
+// when they carry a number, e.g. a tick count. `frozen` is read from any reply.
+pub struct TickCommandResponse {
+    pub meta: CommandMeta,
+    pub rate: Option<f32>,
+    pub frozen: bool,
+}
+
+impl Command for TickCommand {
+    type Response = TickCommandResponse;
+}
+
+impl From<TickCommand> for CommandRequestPacket {
+    fn from(value: TickCommand) -> Self {
+        Self::new(&match value {
+            TickCommand::Query => "tick query".to_string(),
+            TickCommand::Rate { rate } => format!("tick rate {}", rate),
+            TickCommand::Freeze => "tick freeze".to_string(),
+            TickCommand::Unfreeze => "tick unfreeze".to_string(),
+            TickCommand::Step { ticks } => format!("tick step {}", ticks),
+            TickCommand::Sprint { ticks } => format!("tick sprint {}", ticks),
+            TickCommand::SprintStop => "tick sprint stop".to_string(),
+        })
+    }
+}
+
+// Replies read e.g. "Target tick rate: 20.0 per second" or "The game is frozen".
+impl TryFrom<CommandResponsePacket> for TickCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+
+        let has_word = |expected: &str| {
+            message.split_whitespace().any(|word| {
+                word.trim_matches(|c: char| !c.is_alphanumeric())
+                    .eq_ignore_ascii_case(expected)
+            })
+        };
+
+        Ok(Self {
+            meta,
+            rate: has_word("rate")
+                .then(|| parse_enum_from_message(&message))
+                .flatten(),
+            frozen: has_word("frozen"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::status_reply;
+
+    fn parse(message: &str) -> TickCommandResponse {
+        status_reply(0, message).try_into().unwrap()
+    }
+
+    #[test]
+    fn query_reports_rate() {
+        let response = parse("Target tick rate: 20.0 per second");
+        assert_eq!(response.rate, Some(20.0));
+        assert!(!response.frozen);
+    }
+
+    #[test]
+    fn rate_reports_new_rate() {
+        assert_eq!(parse("Set the target tick rate to 40").rate, Some(40.0));
+    }
+
+    #[test]
+    fn step_has_no_rate() {
+        assert_eq!(parse("Stepping 100 ticks").rate, None);
+    }
+
+    #[test]
+    fn freeze_is_reported() {
+        let response = parse("The game is frozen");
+        assert!(response.frozen);
+        assert_eq!(response.rate, None);
+    }
+}
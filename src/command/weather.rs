@@ -0,0 +1,103 @@
+use super::{Command, CommandMeta, parse_enum_from_message};
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Weather {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl fmt::Display for Weather {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Clear => "clear",
+            Self::Rain => "rain",
+            Self::Thunder => "thunder",
+        })
+    }
+}
+
+impl FromStr for Weather {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "clear" => Ok(Self::Clear),
+            "rain" => Ok(Self::Rain),
+            "thunder" => Ok(Self::Thunder),
+            _ => Err(Error::InvalidType),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WeatherQueryCommand;
+
+pub struct WeatherQueryCommandResponse {
+    pub meta: CommandMeta,
+    pub weather: Weather,
+}
+
+impl Command for WeatherQueryCommand {
+    type Response = WeatherQueryCommandResponse;
+}
+
+impl From<WeatherQueryCommand> for CommandRequestPacket {
+    fn from(_: WeatherQueryCommand) -> Self {
+        Self::new("weather query")
+    }
+}
+
+// Replies read "Weather state is: clear".
+impl TryFrom<CommandResponsePacket> for WeatherQueryCommandResponse {
+    type Error = Error;
+
+    fn try_from(value: CommandResponsePacket) -> Result<Self> {
+        let meta = CommandMeta::from(&value);
+        let message = value
+            .status_message
+            .ok_or(Error::MissingField("statusMessage"))?;
+
+        Ok(Self {
+            meta,
+            weather: parse_enum_from_message(&message).ok_or(Error::InvalidType)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::status_reply;
+
+    fn parse(message: &str) -> Result<WeatherQueryCommandResponse> {
+        status_reply(0, message).try_into()
+    }
+
+    #[test]
+    fn weather_is_parsed() {
+        assert_eq!(
+            parse("Weather state is: clear").unwrap().weather,
+            Weather::Clear
+        );
+        assert_eq!(
+            parse("Weather state is: thunder").unwrap().weather,
+            Weather::Thunder
+        );
+    }
+
+    #[test]
+    fn unknown_weather_is_rejected() {
+        assert!(matches!(
+            parse("Weather state is: snow"),
+            Err(Error::InvalidType)
+        ));
+    }
+}
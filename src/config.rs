@@ -0,0 +1,111 @@
+use crate::{packet::OriginType, Hooks};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub max_in_flight_commands: usize,
+    // Applied to every command unless a call passes its own; `None` waits forever.
+    pub command_timeout: Option<Duration>,
+    // Caps how many commands are sent per second; `None` sends them as fast as possible.
+    pub commands_per_second: Option<u32>,
+    // Ends the loop with `Error::IdleTimeout` once nothing has been received for this long.
+    pub idle_timeout: Option<Duration>,
+    // Inbound packets larger than this are skipped and reported as `Error::PacketTooLarge`.
+    pub max_packet_size: Option<usize>,
+    // Skips inbound packets with header entries this crate doesn't know, reporting them
+    // as `Error::UnknownHeaders`, so protocol drift between game versions shows up. Bodies
+    // carry command data and event properties, so they're never checked.
+    pub strict_headers: bool,
+    // Listeners left unpolled for this long unsubscribe themselves and stop yielding
+    // events. Time spent waiting inside `recv` counts as being polled.
+    pub listener_idle_timeout: Option<Duration>,
+    // Capacities of the loop's internal channels. Smaller channels save memory at the cost
+    // of senders waiting sooner; each is at least one.
+    pub command_channel_size: usize,
+    pub packet_channel_size: usize,
+    pub event_channel_size: usize,
+    // Replaces the origin on every outbound command request; `None` sends each with its own.
+    pub default_origin: Option<OriginType>,
+    pub hooks: Hooks,
+}
+
+impl ServerConfig {
+    pub fn with_max_in_flight_commands(mut self, max_in_flight_commands: usize) -> Self {
+        self.max_in_flight_commands = max_in_flight_commands;
+        self
+    }
+
+    pub fn with_command_timeout(mut self, command_timeout: Option<Duration>) -> Self {
+        self.command_timeout = command_timeout;
+        self
+    }
+
+    pub fn with_commands_per_second(mut self, commands_per_second: Option<u32>) -> Self {
+        self.commands_per_second = commands_per_second;
+        self
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    pub fn with_max_packet_size(mut self, max_packet_size: Option<usize>) -> Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    pub fn with_strict_headers(mut self, strict_headers: bool) -> Self {
+        self.strict_headers = strict_headers;
+        self
+    }
+
+    pub fn with_listener_idle_timeout(mut self, listener_idle_timeout: Option<Duration>) -> Self {
+        self.listener_idle_timeout = listener_idle_timeout;
+        self
+    }
+
+    pub fn with_command_channel_size(mut self, command_channel_size: usize) -> Self {
+        self.command_channel_size = command_channel_size;
+        self
+    }
+
+    pub fn with_packet_channel_size(mut self, packet_channel_size: usize) -> Self {
+        self.packet_channel_size = packet_channel_size;
+        self
+    }
+
+    pub fn with_event_channel_size(mut self, event_channel_size: usize) -> Self {
+        self.event_channel_size = event_channel_size;
+        self
+    }
+
+    pub fn with_default_origin(mut self, default_origin: Option<OriginType>) -> Self {
+        self.default_origin = default_origin;
+        self
+    }
+
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight_commands: 100,
+            command_timeout: None,
+            commands_per_second: None,
+            idle_timeout: None,
+            max_packet_size: Some(4 * 1024 * 1024),
+            strict_headers: false,
+            listener_idle_timeout: None,
+            command_channel_size: u16::MAX as usize,
+            packet_channel_size: u16::MAX as usize,
+            event_channel_size: 1024,
+            default_origin: None,
+            hooks: Hooks::default(),
+        }
+    }
+}
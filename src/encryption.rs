@@ -0,0 +1,188 @@
+use crate::{Error, Result};
+use aes::{
+    cipher::{generic_array::GenericArray, BlockDecryptMut, BlockEncryptMut, KeyIvInit},
+    Aes256,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use p384::{
+    ecdh::EphemeralSecret,
+    pkcs8::{DecodePublicKey, EncodePublicKey},
+    PublicKey,
+};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+const SALT_SIZE: usize = 16;
+
+// Bedrock negotiates an ECDH (secp384r1) shared secret, then both sides run one
+// continuous AES-256-CFB8 stream per direction keyed with sha256(salt + secret).
+pub struct KeyExchange {
+    secret: EphemeralSecret,
+    public_key: String,
+    salt: [u8; SALT_SIZE],
+}
+
+impl KeyExchange {
+    pub fn new() -> Result<Self> {
+        let secret = EphemeralSecret::random(&mut OsRng);
+        let public_key = secret
+            .public_key()
+            .to_public_key_der()
+            .map_err(|_| Error::EncryptionFailed("failed to encode public key"))?;
+
+        let mut salt = [0; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+
+        Ok(Self {
+            secret,
+            public_key: STANDARD.encode(public_key.as_bytes()),
+            salt,
+        })
+    }
+
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    pub fn salt(&self) -> String {
+        STANDARD.encode(self.salt)
+    }
+
+    pub fn complete(self, client_public_key: &str) -> Result<Cipher> {
+        let client_public_key = STANDARD
+            .decode(client_public_key)
+            .map_err(|_| Error::EncryptionFailed("client public key is not base64"))?;
+        let client_public_key = PublicKey::from_public_key_der(&client_public_key)
+            .map_err(|_| Error::EncryptionFailed("invalid client public key"))?;
+
+        let shared_secret = self.secret.diffie_hellman(&client_public_key);
+
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt);
+        hasher.update(shared_secret.raw_secret_bytes());
+        let key = hasher.finalize();
+
+        Cipher::new(&key, &key[..16])
+    }
+}
+
+pub struct Cipher {
+    encryptor: cfb8::Encryptor<Aes256>,
+    decryptor: cfb8::Decryptor<Aes256>,
+}
+
+impl Cipher {
+    // The key must be 32 bytes and the IV 16.
+    pub fn new(key: &[u8], iv: &[u8]) -> Result<Self> {
+        let invalid_length = |_| Error::EncryptionFailed("invalid key or iv length");
+
+        Ok(Self {
+            encryptor: cfb8::Encryptor::new_from_slices(key, iv).map_err(invalid_length)?,
+            decryptor: cfb8::Decryptor::new_from_slices(key, iv).map_err(invalid_length)?,
+        })
+    }
+
+    pub fn encrypt(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut data = data.to_vec();
+        for byte in data.chunks_exact_mut(1) {
+            self.encryptor
+                .encrypt_block_mut(GenericArray::from_mut_slice(byte));
+        }
+        data
+    }
+
+    pub fn decrypt(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut data = data.to_vec();
+        for byte in data.chunks_exact_mut(1) {
+            self.decryptor
+                .decrypt_block_mut(GenericArray::from_mut_slice(byte));
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NIST SP 800-38A, F.3.17 (CFB8-AES256.Encrypt).
+    const KEY: [u8; 32] = [
+        0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77,
+        0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14,
+        0xdf, 0xf4,
+    ];
+    const IV: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+    const PLAINTEXT: [u8; 18] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17,
+        0x2a, 0xae, 0x2d,
+    ];
+    const CIPHERTEXT: [u8; 18] = [
+        0xdc, 0x1f, 0x1a, 0x85, 0x20, 0xa6, 0x4d, 0xb5, 0x5f, 0xcc, 0x8a, 0xc5, 0x54, 0x84, 0x4e,
+        0x88, 0x97, 0x00,
+    ];
+
+    #[test]
+    fn matches_known_answer() {
+        let mut cipher = Cipher::new(&KEY, &IV).unwrap();
+        assert_eq!(cipher.encrypt(&PLAINTEXT), CIPHERTEXT);
+        assert_eq!(cipher.decrypt(&CIPHERTEXT), PLAINTEXT);
+    }
+
+    #[test]
+    fn stream_continues_across_frames() {
+        let mut cipher = Cipher::new(&KEY, &IV).unwrap();
+        let mut ciphertext = cipher.encrypt(&PLAINTEXT[..5]);
+        ciphertext.extend(cipher.encrypt(&PLAINTEXT[5..]));
+        assert_eq!(ciphertext, CIPHERTEXT);
+
+        let mut plaintext = cipher.decrypt(&CIPHERTEXT[..11]);
+        plaintext.extend(cipher.decrypt(&CIPHERTEXT[11..]));
+        assert_eq!(plaintext, PLAINTEXT);
+    }
+
+    #[test]
+    fn both_sides_derive_the_same_key() {
+        let server = KeyExchange::new().unwrap();
+        let salt = STANDARD.decode(server.salt()).unwrap();
+        let server_public_key =
+            PublicKey::from_public_key_der(&STANDARD.decode(server.public_key()).unwrap()).unwrap();
+
+        // The game's half of the exchange.
+        let client_secret = EphemeralSecret::random(&mut OsRng);
+        let client_public_key = client_secret.public_key().to_public_key_der().unwrap();
+        let shared_secret = client_secret.diffie_hellman(&server_public_key);
+        let mut hasher = Sha256::new();
+        hasher.update(&salt);
+        hasher.update(shared_secret.raw_secret_bytes());
+        let key = hasher.finalize();
+        let mut client = Cipher::new(&key, &key[..16]).unwrap();
+
+        let mut server = server
+            .complete(&STANDARD.encode(client_public_key.as_bytes()))
+            .unwrap();
+        let message = b"{\"header\":{},\"body\":{}}";
+        assert_eq!(server.decrypt(&client.encrypt(message)), message);
+        assert_eq!(client.decrypt(&server.encrypt(message)), message);
+    }
+
+    #[test]
+    fn rejects_invalid_public_keys() {
+        assert!(KeyExchange::new().unwrap().complete("not base64!").is_err());
+        assert!(KeyExchange::new().unwrap().complete("AAAA").is_err());
+    }
+
+    #[test]
+    fn wrong_lengths_are_rejected() {
+        assert!(matches!(
+            Cipher::new(&KEY[..16], &IV),
+            Err(Error::EncryptionFailed(_))
+        ));
+        assert!(matches!(
+            Cipher::new(&KEY, &IV[..8]),
+            Err(Error::EncryptionFailed(_))
+        ));
+    }
+}
@@ -1,18 +1,23 @@
 use std::{fmt, result};
 use thiserror::Error;
-use tokio::sync::{mpsc, oneshot, watch, AcquireError};
+use tokio::sync::{broadcast, mpsc, oneshot, AcquireError};
 use tokio_tungstenite::tungstenite;
 use uuid::Uuid;
 
-use crate::packet::{CommandResponsePacket, EventPacket, Packet};
+use crate::{
+    event::EventType,
+    packet::{CommandResponsePacket, EventPacket, Packet},
+};
 
 pub type Result<T> = result::Result<T, Error>;
 pub type MultiResult<T> = result::Result<T, MultiError>;
 
 #[derive(Error, Debug)]
 pub enum Error {
+    #[error("io error")]
+    IoError(#[from] std::io::Error),
     #[error("websocket error")]
-    WebsocketError(#[from] tungstenite::Error),
+    WebsocketError(#[source] Box<tungstenite::Error>),
     #[error("json parsing error")]
     JsonParseError(#[from] serde_json::Error),
     #[error("{0} stream exhausted")]
@@ -22,24 +27,39 @@ pub enum Error {
         status_message: Option<String>,
         status_code: i32,
     },
+    #[error("connection closed by peer: {code:?} {reason:?}")]
+    ConnectionClosed { code: Option<u16>, reason: String },
     #[error("event loop not running")]
     LoopNotRunning,
+    #[error("event loop error was already taken")]
+    LoopErrorTaken,
+    #[error("command {0} timed out")]
+    CommandTimedOut(Uuid),
+    #[error("command {0} was cancelled")]
+    CommandCancelled(Uuid),
+    #[error("no message received within the idle timeout")]
+    IdleTimeout,
+    #[error("peer did not acknowledge the close handshake")]
+    CloseNotAcknowledged,
+    #[error("server is shutting down")]
+    ServerShuttingDown,
+    #[error("no command permits available")]
+    WouldBlock,
     #[error("failed to broadcast event")]
-    EventBroadcastFailed(#[from] watch::error::SendError<EventPacket>),
+    EventBroadcastFailed(#[source] Box<broadcast::error::SendError<EventPacket>>),
     #[error("failed to receive event")]
-    EventReceiveFailed(#[from] watch::error::RecvError),
+    EventReceiveFailed(#[from] broadcast::error::RecvError),
+    #[error("event listener fell behind and skipped {0} events")]
+    Lagged(u64),
     #[error("failed to send packet")]
-    PacketSendFailed(#[from] mpsc::error::SendError<Packet>),
+    PacketSendFailed(#[source] Box<mpsc::error::SendError<Packet>>),
     #[error("failed to send command")]
     CommandSendFailed(
         #[from]
-        mpsc::error::SendError<(
-            Uuid,
-            oneshot::Sender<CommandResponsePacket>,
-        )>,
+        mpsc::error::SendError<(Uuid, oneshot::Sender<Result<CommandResponsePacket>>)>,
     ),
     #[error("unexpected packet: {0:?}")]
-    UnexpectedPacket(Packet),
+    UnexpectedPacket(Box<Packet>),
     #[error("failed to handle command")]
     CommandHandlingError,
     #[error("failed to obtain semaphore")]
@@ -48,8 +68,41 @@ pub enum Error {
     CommandResponseNeverBroadcasted(#[from] oneshot::error::RecvError),
     #[error("missing field: {0}")]
     MissingField(&'static str),
+    #[error("expected {expected:?} event, got {actual:?}")]
+    EventTypeMismatch {
+        expected: EventType,
+        actual: EventType,
+    },
     #[error("invalid type")]
     InvalidType,
+    #[error("unknown header entries: {}", .0.join(", "))]
+    UnknownHeaders(Vec<String>),
+    #[error("packet of {size} bytes exceeds the {limit} byte limit")]
+    PacketTooLarge { size: usize, limit: usize },
+    #[error("unexpected binary frame of {} bytes", .0.len())]
+    UnexpectedBinaryFrame(Vec<u8>),
+    #[cfg(feature = "encryption")]
+    #[error("encryption failed: {0}")]
+    EncryptionFailed(&'static str),
+}
+
+// The larger payloads are boxed so every `Result` in the crate stays small.
+impl From<tungstenite::Error> for Error {
+    fn from(value: tungstenite::Error) -> Self {
+        Self::WebsocketError(Box::new(value))
+    }
+}
+
+impl From<broadcast::error::SendError<EventPacket>> for Error {
+    fn from(value: broadcast::error::SendError<EventPacket>) -> Self {
+        Self::EventBroadcastFailed(Box::new(value))
+    }
+}
+
+impl From<mpsc::error::SendError<Packet>> for Error {
+    fn from(value: mpsc::error::SendError<Packet>) -> Self {
+        Self::PacketSendFailed(Box::new(value))
+    }
 }
 
 #[derive(Debug)]
@@ -62,6 +115,29 @@ pub enum MultiError {
     },
 }
 
+impl MultiError {
+    // A failing loop usually takes the handler down with it, so its error wins.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Self::LoopErrored(error) | Self::HandlerErrored(error) => error,
+            Self::BothErrored { loop_error, .. } => loop_error,
+        }
+    }
+
+    // Whether the root cause was the connection itself failing or going away.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self.root_cause(),
+            Error::IoError(_)
+                | Error::WebsocketError(_)
+                | Error::ConnectionClosed { .. }
+                | Error::StreamExhausted(_)
+                | Error::LoopNotRunning
+                | Error::IdleTimeout
+        )
+    }
+}
+
 impl std::error::Error for MultiError {}
 impl fmt::Display for MultiError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -78,3 +154,48 @@ impl fmt::Display for MultiError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minecraft_error() -> Error {
+        Error::MinecraftError {
+            status_message: None,
+            status_code: -1,
+        }
+    }
+
+    #[test]
+    fn loop_errors_are_their_own_root_cause() {
+        let error = MultiError::LoopErrored(Error::IdleTimeout);
+        assert!(matches!(error.root_cause(), Error::IdleTimeout));
+        assert!(error.is_connection_error());
+    }
+
+    #[test]
+    fn handler_errors_are_their_own_root_cause() {
+        let error = MultiError::HandlerErrored(minecraft_error());
+        assert!(matches!(error.root_cause(), Error::MinecraftError { .. }));
+        assert!(!error.is_connection_error());
+    }
+
+    #[test]
+    fn the_loop_error_wins_when_both_fail() {
+        let error = MultiError::BothErrored {
+            loop_error: Error::ConnectionClosed {
+                code: Some(1001),
+                reason: String::new(),
+            },
+            handler_error: Error::LoopNotRunning,
+        };
+        assert!(matches!(error.root_cause(), Error::ConnectionClosed { .. }));
+        assert!(error.is_connection_error());
+
+        let error = MultiError::BothErrored {
+            loop_error: minecraft_error(),
+            handler_error: Error::LoopNotRunning,
+        };
+        assert!(!error.is_connection_error());
+    }
+}
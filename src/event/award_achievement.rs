@@ -0,0 +1,30 @@
+use super::Player;
+use serde::Deserialize;
+
+crate::event! {
+    AwardAchievement =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AwardAchievement {
+        pub player: Player,
+        pub achievement_id: i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse_event;
+    use serde_json::json;
+
+    #[test]
+    fn achievements_deserialize() {
+        let event: AwardAchievement = parse_event(json!({
+            "player": { "name": "Steve" },
+            "achievementId": 7,
+        }));
+
+        assert_eq!(event.player.name, "Steve");
+        assert_eq!(event.achievement_id, 7);
+    }
+}
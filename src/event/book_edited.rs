@@ -0,0 +1,13 @@
+use super::Player;
+use serde::Deserialize;
+
+crate::event! {
+    BookEdited =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BookEdited {
+        pub player: Player,
+        pub pages_added: i32,
+        pub sign_book: bool,
+    }
+}
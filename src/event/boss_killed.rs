@@ -0,0 +1,33 @@
+use super::Player;
+use serde::Deserialize;
+
+crate::event! {
+    BossKilled =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BossKilled {
+        pub player: Player,
+        pub boss_type: i32,
+        pub party_size: u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse_event;
+    use serde_json::json;
+
+    #[test]
+    fn boss_kills_deserialize() {
+        let event: BossKilled = parse_event(json!({
+            "player": { "name": "Steve" },
+            "bossType": 53,
+            "partySize": 3,
+        }));
+
+        assert_eq!(event.player.name, "Steve");
+        assert_eq!(event.boss_type, 53);
+        assert_eq!(event.party_size, 3);
+    }
+}
@@ -0,0 +1,13 @@
+use super::{Entity, Player};
+use serde::Deserialize;
+
+crate::event! {
+    EntitySpawned =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EntitySpawned {
+        pub player: Player,
+        pub mob: Entity,
+        pub spawn_type: Option<i32>,
+    }
+}
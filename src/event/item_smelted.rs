@@ -0,0 +1,34 @@
+use super::{ItemStack, Player};
+use serde::Deserialize;
+
+crate::event! {
+    ItemSmelted =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ItemSmelted {
+        pub player: Player,
+        pub item: ItemStack,
+        pub fuel_source: ItemStack,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse_event;
+    use serde_json::json;
+
+    #[test]
+    fn furnace_smelts_deserialize() {
+        let event: ItemSmelted = parse_event(json!({
+            "player": { "name": "Steve", "id": -4294967295i64, "dimension": 0 },
+            "item": { "id": "iron_ingot", "namespace": "minecraft", "aux": 0 },
+            "fuelSource": { "id": "coal", "namespace": "minecraft", "aux": 0 },
+        }));
+
+        assert_eq!(event.player.name, "Steve");
+        assert_eq!(event.item.id, "iron_ingot");
+        assert_eq!(event.fuel_source.id, "coal");
+        assert_eq!(event.fuel_source.namespace.as_deref(), Some("minecraft"));
+    }
+}
@@ -0,0 +1,60 @@
+// Declares an event struct and its `Event` impl. The struct still derives
+// `Deserialize` itself, so it keeps full control over serde attributes.
+//
+// event! {
+//     PlayerMessage =>
+//     #[derive(Deserialize, Debug)]
+//     pub struct PlayerMessage { pub message: String }
+// }
+#[macro_export]
+macro_rules! event {
+    (
+        $event_type:ident =>
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident { $($fields:tt)* }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name { $($fields)* }
+
+        impl $crate::event::Event for $name {
+            fn get_type() -> $crate::event::EventType {
+                $crate::event::EventType::$event_type
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        event::{Event, EventType},
+        packet::Packet,
+    };
+    use serde::Deserialize;
+
+    crate::event! {
+        EndOfDay =>
+        #[derive(Deserialize, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct EndOfDay {
+            pub day_count: u32,
+        }
+    }
+
+    #[test]
+    fn declared_events_know_their_type() {
+        assert_eq!(EndOfDay::get_type(), EventType::EndOfDay);
+    }
+
+    #[test]
+    fn declared_events_deserialize_from_packets() {
+        let json = r#"{
+            "header": { "version": 1, "messagePurpose": "event", "eventName": "EndOfDay" },
+            "body": { "dayCount": 12 }
+        }"#;
+        let Packet::Event(event) = Packet::from_json(json).unwrap() else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.deserialize_as::<EndOfDay>().unwrap().day_count, 12);
+    }
+}
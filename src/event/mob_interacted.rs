@@ -0,0 +1,13 @@
+use super::{Entity, Player};
+use serde::Deserialize;
+
+crate::event! {
+    MobInteracted =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MobInteracted {
+        pub player: Player,
+        pub mob: Entity,
+        pub interaction_type: Option<i32>,
+    }
+}
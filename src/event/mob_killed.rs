@@ -0,0 +1,13 @@
+use super::{Entity, Player};
+use serde::Deserialize;
+
+crate::event! {
+    MobKilled =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MobKilled {
+        pub player: Player,
+        pub victim: Entity,
+        pub kill_method_type: Option<i32>,
+    }
+}
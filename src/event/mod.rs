@@ -1,33 +1,154 @@
+mod award_achievement;
+mod book_edited;
+mod boss_killed;
+mod entity_spawned;
+mod item_smelted;
+mod macros;
+mod mob_interacted;
+mod mob_killed;
+mod player_bounced;
+mod player_died;
+mod player_level_change;
 mod player_message;
+mod portal_used;
 mod types;
 
-pub use player_message::PlayerMessage;
-pub use types::EventType;
+pub use award_achievement::AwardAchievement;
+pub use book_edited::BookEdited;
+pub use boss_killed::BossKilled;
+pub use entity_spawned::EntitySpawned;
+pub use item_smelted::ItemSmelted;
+pub use mob_interacted::MobInteracted;
+pub use mob_killed::MobKilled;
+pub use player_bounced::PlayerBounced;
+pub use player_died::PlayerDied;
+pub use player_level_change::PlayerLevelChange;
+pub use player_message::{MessageType, PlayerMessage};
+pub use portal_used::PortalUsed;
+pub use types::{Block, Entity, EventType, ItemStack, Player, Position};
 
 use crate::{
     packet::{EventPacket, Packet, UnsubscribePacket},
-    Error, Result,
+    trace, Error, Result,
 };
+use futures::{stream, Stream};
 use serde::de::DeserializeOwned;
-use serde_json::Value;
 use std::{
+    collections::BTreeMap,
     marker::PhantomData,
     sync::{
-        atomic::{AtomicU32, Ordering},
-        Arc,
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
     },
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc,
+    time::{sleep_until, Instant},
+};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt,
 };
-use tokio::sync::mpsc;
-use tokio_stream::{wrappers::WatchStream, StreamExt};
 
 pub trait Event: DeserializeOwned {
     fn get_type() -> EventType;
 }
 
-pub struct EventListener<T: Event> {
-    ref_count: Arc<AtomicU32>,
+// Live listeners per event. Counts are only changed under the lock, and the matching
+// subscribe/unsubscribe packets are queued under it too, so they can't go out of order.
+pub(crate) type SubscribedEvents = Arc<Mutex<BTreeMap<EventType, u32>>>;
+
+// Shared between a listener and its idle watchdog, so whichever lets go first drops
+// the reference count exactly once.
+struct Subscription {
+    event_name: EventType,
+    subscribed_events: SubscribedEvents,
     packet_sender: mpsc::Sender<Packet>,
-    event_receiver: WatchStream<EventPacket>,
+    released: AtomicBool,
+    polling: AtomicBool,
+    last_polled: Mutex<Instant>,
+}
+
+impl Subscription {
+    fn release(&self) {
+        if self.released.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut subscribed_events = self.subscribed_events.lock().unwrap();
+        // The entry is gone if the server closed and cleared every subscription.
+        let Some(ref_count) = subscribed_events.get_mut(&self.event_name) else {
+            return;
+        };
+
+        *ref_count -= 1;
+        if *ref_count == 0 {
+            subscribed_events.remove(&self.event_name);
+            trace::debug!(event_name = ?self.event_name, "unsubscribing");
+            let result = self
+                .packet_sender
+                .try_send(Packet::Unsubscribe(UnsubscribePacket {
+                    event_name: self.event_name,
+                }));
+
+            if let Err(_err) = result {
+                trace::warn!(event_name = ?self.event_name, error = %_err, "failed to send unsubscribe");
+            }
+        }
+    }
+
+    fn idle_deadline(&self, idle_timeout: Duration) -> Instant {
+        if self.polling.load(Ordering::SeqCst) {
+            Instant::now() + idle_timeout
+        } else {
+            *self.last_polled.lock().unwrap() + idle_timeout
+        }
+    }
+
+    async fn watch_idle(subscription: Weak<Subscription>, idle_timeout: Duration) {
+        loop {
+            let deadline = match subscription.upgrade() {
+                Some(subscription) if !subscription.released.load(Ordering::SeqCst) => {
+                    subscription.idle_deadline(idle_timeout)
+                }
+                _ => return,
+            };
+            sleep_until(deadline).await;
+
+            let Some(subscription) = subscription.upgrade() else {
+                return;
+            };
+            if subscription.idle_deadline(idle_timeout) <= Instant::now() {
+                trace::debug!(event_name = ?subscription.event_name, "event listener idle");
+                subscription.release();
+                return;
+            }
+        }
+    }
+}
+
+// Marks the listener as polled for as long as a `recv` call is in progress, including
+// when that call is cancelled.
+struct PollGuard<'a>(&'a Subscription);
+
+impl<'a> PollGuard<'a> {
+    fn new(subscription: &'a Subscription) -> Self {
+        subscription.polling.store(true, Ordering::SeqCst);
+        Self(subscription)
+    }
+}
+
+impl Drop for PollGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.last_polled.lock().unwrap() = Instant::now();
+        self.0.polling.store(false, Ordering::SeqCst);
+    }
+}
+
+pub struct EventListener<T: Event> {
+    subscription: Arc<Subscription>,
+    event_receiver: BroadcastStream<EventPacket>,
     _phantom: PhantomData<T>,
 }
 
@@ -37,40 +158,73 @@ impl<T: Event> EventListener<T> {
     }
 
     pub(crate) fn new_unchecked(
-        ref_count: Arc<AtomicU32>,
+        subscribed_events: SubscribedEvents,
         packet_sender: mpsc::Sender<Packet>,
-        event_receiver: WatchStream<EventPacket>,
+        event_receiver: BroadcastStream<EventPacket>,
+        idle_timeout: Option<Duration>,
     ) -> EventListener<T> {
-        EventListener {
-            ref_count,
+        let subscription = Arc::new(Subscription {
+            event_name: T::get_type(),
+            subscribed_events,
             packet_sender,
+            released: AtomicBool::new(false),
+            polling: AtomicBool::new(false),
+            last_polled: Mutex::new(Instant::now()),
+        });
+
+        if let Some(idle_timeout) = idle_timeout {
+            tokio::spawn(Subscription::watch_idle(
+                Arc::downgrade(&subscription),
+                idle_timeout,
+            ));
+        }
+
+        EventListener {
+            subscription,
             event_receiver,
             _phantom: PhantomData,
         }
     }
 
+    // A listener that falls too far behind gets `Error::Lagged` once with the number of
+    // events it missed, then carries on from the oldest event still buffered.
     pub async fn recv(&mut self) -> Result<T> {
-        while let Some(event) = self.event_receiver.next().await {
+        let _guard = PollGuard::new(&self.subscription);
+
+        // An idle listener has already unsubscribed, so nothing more is coming.
+        while !self.subscription.released.load(Ordering::SeqCst) {
+            let Some(event) = self.event_receiver.next().await else {
+                break;
+            };
+            let event = match event {
+                Ok(event) => event,
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    trace::warn!(event_name = ?T::get_type(), skipped, "event listener lagged");
+                    return Err(Error::Lagged(skipped));
+                }
+            };
+
             if event.event_name == T::get_type() {
-                return Ok(serde_json::from_value(Value::Object(
-                    event.properties,
-                ))?);
+                return event.deserialize_as();
             }
         }
 
         Err(Error::StreamExhausted("event"))
     }
+
+    // Ends once the listener is exhausted; the subscription lasts as long as the stream.
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        stream::unfold(self, |mut listener| async {
+            match listener.recv().await {
+                Err(Error::StreamExhausted(_)) => None,
+                event => Some((event, listener)),
+            }
+        })
+    }
 }
 
 impl<T: Event> Drop for EventListener<T> {
     fn drop(&mut self) {
-        let val = self.ref_count.fetch_sub(1, Ordering::SeqCst);
-        if val == 1 {
-            self.packet_sender
-                .try_send(Packet::Unsubscribe(UnsubscribePacket {
-                    event_name: T::get_type(),
-                }))
-                .ok();
-        }
+        self.subscription.release();
     }
 }
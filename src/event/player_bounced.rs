@@ -0,0 +1,33 @@
+use super::{Block, Player};
+use serde::Deserialize;
+
+crate::event! {
+    PlayerBounced =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PlayerBounced {
+        pub player: Player,
+        pub block: Block,
+        pub bounce_height: f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse_event;
+    use serde_json::json;
+
+    #[test]
+    fn slime_bounces_deserialize() {
+        let event: PlayerBounced = parse_event(json!({
+            "player": { "name": "Steve" },
+            "block": { "id": "slime", "namespace": "minecraft", "aux": 0 },
+            "bounceHeight": 4.5,
+        }));
+
+        assert_eq!(event.player.name, "Steve");
+        assert_eq!(event.block.id, "slime");
+        assert_eq!(event.bounce_height, 4.5);
+    }
+}
@@ -0,0 +1,13 @@
+use super::{Entity, Player};
+use serde::Deserialize;
+
+crate::event! {
+    PlayerDied =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PlayerDied {
+        pub player: Player,
+        pub killer: Option<Entity>,
+        pub cause: Option<i32>,
+    }
+}
@@ -0,0 +1,13 @@
+use super::Player;
+use serde::Deserialize;
+
+crate::event! {
+    PlayerLevelChange =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PlayerLevelChange {
+        pub player: Player,
+        pub old_level: i32,
+        pub new_level: i32,
+    }
+}
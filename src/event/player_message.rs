@@ -1,26 +1,95 @@
-use super::{Event, EventType};
 use serde::Deserialize;
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
 pub enum MessageType {
     Chat,
     Say,
     Tell,
+    Me,
+    Title,
+    Other(String),
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct PlayerMessage {
-    pub message: String,
-    #[serde(rename = "type")]
-    pub message_type: MessageType,
-    pub sender: String,
-    pub receiver: String,
+impl From<String> for MessageType {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "chat" => Self::Chat,
+            "say" => Self::Say,
+            "tell" => Self::Tell,
+            "me" => Self::Me,
+            "title" => Self::Title,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+crate::event! {
+    PlayerMessage =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PlayerMessage {
+        pub message: String,
+        #[serde(rename = "type")]
+        pub message_type: MessageType,
+        pub sender: String,
+        pub receiver: String,
+    }
+}
+
+impl PlayerMessage {
+    // Chat and `say` messages reach everyone and arrive with no receiver.
+    pub fn is_broadcast(&self) -> bool {
+        matches!(self.receiver.as_str(), "" | "*" | "@a")
+    }
+
+    // Broadcasts count as directed at everyone; player names are case-insensitive.
+    pub fn is_directed_to(&self, name: &str) -> bool {
+        self.is_broadcast() || self.receiver.eq_ignore_ascii_case(name)
+    }
 }
 
-impl Event for PlayerMessage {
-    fn get_type() -> EventType {
-        EventType::PlayerMessage
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(message_type: &str, receiver: &str) -> PlayerMessage {
+        serde_json::from_value(json!({
+            "message": "hi",
+            "type": message_type,
+            "sender": "Steve",
+            "receiver": receiver,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn me_messages_deserialize() {
+        assert_eq!(message("me", "").message_type, MessageType::Me);
+    }
+
+    #[test]
+    fn unknown_types_fall_into_other() {
+        assert_eq!(
+            message("announcement", "").message_type,
+            MessageType::Other("announcement".to_string())
+        );
+    }
+
+    #[test]
+    fn chat_and_say_reach_everyone() {
+        for message in [message("chat", ""), message("say", "")] {
+            assert!(message.is_broadcast());
+            assert!(message.is_directed_to("Alex"));
+        }
+    }
+
+    #[test]
+    fn tells_reach_only_their_receiver() {
+        let message = message("tell", "Alex");
+        assert!(!message.is_broadcast());
+        assert!(message.is_directed_to("alex"));
+        assert!(!message.is_directed_to("Notch"));
     }
 }
@@ -0,0 +1,33 @@
+use super::Player;
+use serde::Deserialize;
+
+crate::event! {
+    PortalUsed =>
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PortalUsed {
+        pub player: Player,
+        pub from_dimension_id: i32,
+        pub to_dimension_id: i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse_event;
+    use serde_json::json;
+
+    #[test]
+    fn nether_trips_deserialize() {
+        let event: PortalUsed = parse_event(json!({
+            "player": { "name": "Steve", "dimension": 0 },
+            "fromDimensionId": 0,
+            "toDimensionId": 1,
+        }));
+
+        assert_eq!(event.player.name, "Steve");
+        assert_eq!(event.from_dimension_id, 0);
+        assert_eq!(event.to_dimension_id, 1);
+    }
+}
@@ -15,6 +15,7 @@ pub enum EventType {
     BlockBroken,
     BlockPlaced,
     BoardTextUpdated,
+    BookEdited,
     BossKilled,
     CameraUsed,
     CauldronUsed,
@@ -61,6 +62,7 @@ pub enum EventType {
     PlayerDied,
     PlayerJoin,
     PlayerLeave,
+    PlayerLevelChange,
     #[default]
     PlayerMessage,
     PlayerTeleported,
@@ -93,3 +95,137 @@ pub enum EventType {
     WorldLoaded,
     WorldUnloaded,
 }
+
+// Only `name` is reliably present; the rest depends on the event and client version.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Player {
+    pub name: String,
+    #[serde(default)]
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub dimension: Option<i32>,
+    #[serde(default)]
+    pub position: Option<Position>,
+    #[serde(default)]
+    pub variant: Option<i32>,
+    #[serde(default)]
+    pub y_rot: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemStack {
+    pub id: String,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub aux: i32,
+    #[serde(default)]
+    pub stack_size: Option<u32>,
+    #[serde(default)]
+    pub max_stack_size: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    pub id: String,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub aux: i32,
+}
+
+// Mobs and players are described differently depending on the event, so every
+// field is optional.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Entity {
+    #[serde(default)]
+    pub id: Option<i64>,
+    #[serde(default, rename = "type")]
+    pub entity_type: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub position: Option<Position>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimension: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::MobKilled, test_util::parse_event};
+    use serde_json::json;
+
+    #[test]
+    fn mob_entities_deserialize() {
+        let entity: Entity = serde_json::from_value(json!({
+            "id": 123,
+            "type": "minecraft:zombie",
+            "color": "green",
+            "position": { "x": 1.0, "y": 64.0, "z": -3.5 },
+        }))
+        .unwrap();
+
+        assert_eq!(entity.id, Some(123));
+        assert_eq!(entity.entity_type.as_deref(), Some("minecraft:zombie"));
+        assert_eq!(entity.color.as_deref(), Some("green"));
+        assert_eq!(entity.position.map(|position| position.z), Some(-3.5));
+    }
+
+    #[test]
+    fn sparse_entities_deserialize() {
+        let entity: Entity = serde_json::from_value(json!({ "name": "Steve" })).unwrap();
+        assert_eq!(entity.name.as_deref(), Some("Steve"));
+        assert_eq!(entity.id, None);
+        assert_eq!(entity.entity_type, None);
+    }
+
+    #[test]
+    fn kill_events_embed_an_entity() {
+        let event: MobKilled = parse_event(json!({
+            "player": { "name": "Steve" },
+            "victim": { "id": 7, "type": "minecraft:skeleton" },
+        }));
+
+        assert_eq!(
+            event.victim.entity_type.as_deref(),
+            Some("minecraft:skeleton")
+        );
+        assert_eq!(event.kill_method_type, None);
+    }
+
+    #[test]
+    fn positions_deserialize_with_or_without_a_dimension() {
+        let position: Position =
+            serde_json::from_value(json!({ "x": 1.5, "y": 64.0, "z": -2.0, "dimension": 1 }))
+                .unwrap();
+        assert_eq!(
+            position,
+            Position {
+                x: 1.5,
+                y: 64.0,
+                z: -2.0,
+                dimension: Some(1),
+            }
+        );
+
+        let position: Position =
+            serde_json::from_value(json!({ "x": 0.0, "y": 0.0, "z": 0.0 })).unwrap();
+        assert_eq!(position.dimension, None);
+    }
+}
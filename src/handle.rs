@@ -0,0 +1,977 @@
+use crate::{
+    command::Command,
+    event::{Event, EventListener, EventType, SubscribedEvents},
+    packet::{CommandRequestPacket, CommandResponsePacket, EventPacket, Packet, SubscribePacket},
+    rate_limit::RateLimiter,
+    server::{Control, SentCommand},
+    trace, Error, Result, ServerConfig,
+};
+use futures::{future::join_all, Stream};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::{
+    broadcast, mpsc, oneshot, Semaphore, SemaphorePermit, TryAcquireError,
+};
+use tokio_stream::wrappers::BroadcastStream;
+use uuid::Uuid;
+
+// A cheap, cloneable view of a running `Server` that can be moved into other tasks.
+// Every clone shares the same subscriptions and in-flight command limit.
+pub struct ServerHandle {
+    // Buffers every event since this handle was created, for `drain_raw_events`. It's
+    // also resubscribed for listeners, which keeps the channel open while a handle lives.
+    event_receiver: broadcast::Receiver<EventPacket>,
+    command_sender: mpsc::Sender<SentCommand>,
+    packet_sender: mpsc::Sender<Packet>,
+    unexpected_sender: broadcast::Sender<Packet>,
+    control_sender: mpsc::Sender<Control>,
+    subscribed_events: SubscribedEvents,
+    command_semaphore: Arc<Semaphore>,
+    command_timeout: Option<Duration>,
+    listener_idle_timeout: Option<Duration>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl ServerHandle {
+    pub(crate) fn new(
+        event_receiver: broadcast::Receiver<EventPacket>,
+        command_sender: mpsc::Sender<SentCommand>,
+        packet_sender: mpsc::Sender<Packet>,
+        unexpected_sender: broadcast::Sender<Packet>,
+        control_sender: mpsc::Sender<Control>,
+        config: &ServerConfig,
+    ) -> Self {
+        Self {
+            event_receiver,
+            command_sender,
+            packet_sender,
+            unexpected_sender,
+            control_sender,
+            subscribed_events: Arc::new(Mutex::new(BTreeMap::new())),
+            command_semaphore: Arc::new(Semaphore::new(config.max_in_flight_commands)),
+            command_timeout: config.command_timeout,
+            listener_idle_timeout: config.listener_idle_timeout,
+            rate_limiter: config
+                .commands_per_second
+                .map(|rate| Arc::new(RateLimiter::new(rate))),
+        }
+    }
+
+    // The loop drops its receivers on exit, so a closed command channel means it's gone.
+    pub fn is_running(&self) -> bool {
+        !self.command_sender.is_closed()
+    }
+
+    fn assert_running(&self) -> Result<()> {
+        if self.is_running() {
+            Ok(())
+        } else {
+            Err(Error::LoopNotRunning)
+        }
+    }
+
+    pub async fn recv_raw_event(&self) -> Result<EventPacket> {
+        self.assert_running()?;
+        Ok(self.event_receiver.resubscribe().recv().await?)
+    }
+
+    // Events that were overwritten before being drained are skipped.
+    pub fn drain_raw_events(&mut self) -> Vec<EventPacket> {
+        let mut events = vec![];
+
+        loop {
+            match self.event_receiver.try_recv() {
+                Ok(event) => events.push(event),
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => return events,
+            }
+        }
+    }
+
+    pub fn unexpected_packets(&self) -> broadcast::Receiver<Packet> {
+        self.unexpected_sender.subscribe()
+    }
+
+    // Writes the packet as-is, bypassing subscription counts and command tracking, so
+    // the response to a raw command request shows up in `unexpected_packets`.
+    pub async fn send_raw_packet(&self, packet: Packet) -> Result<()> {
+        self.assert_running()?;
+        Ok(self.packet_sender.send(packet).await?)
+    }
+
+    pub async fn send_raw_command(
+        &self,
+        command: CommandRequestPacket,
+    ) -> Result<CommandResponsePacket> {
+        self.send_raw_command_with_timeout(command, self.command_timeout)
+            .await
+    }
+
+    pub async fn send_raw_command_with_timeout(
+        &self,
+        command: CommandRequestPacket,
+        timeout: Option<Duration>,
+    ) -> Result<CommandResponsePacket> {
+        let request_id = command.request_id;
+        Self::with_timeout(request_id, timeout, self.dispatch_command(command)).await
+    }
+
+    async fn wait_for_rate_limit(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+    }
+
+    async fn with_timeout<F: Future<Output = Result<CommandResponsePacket>>>(
+        request_id: Uuid,
+        timeout: Option<Duration>,
+        future: F,
+    ) -> Result<CommandResponsePacket> {
+        match timeout {
+            // Dropping the timed out future tells the loop to forget its waiter.
+            Some(timeout) => tokio::time::timeout(timeout, future)
+                .await
+                .map_err(|_| Error::CommandTimedOut(request_id))?,
+            None => future.await,
+        }
+    }
+
+    async fn dispatch_command(
+        &self,
+        command: CommandRequestPacket,
+    ) -> Result<CommandResponsePacket> {
+        self.assert_running()?;
+        self.wait_for_rate_limit().await;
+
+        // Take a permit before a slot in the outbound channel. A reserved slot can't be
+        // used by anything else, so holding one while waiting for a permit would stall
+        // subscriptions and raw packets behind commands that aren't allowed out yet.
+        // `Server::close` closes the semaphore, which is the only way acquiring fails.
+        let permit = self
+            .command_semaphore
+            .acquire()
+            .await
+            .map_err(|_| Error::ServerShuttingDown)?;
+        let packet_slot = self
+            .packet_sender
+            .reserve()
+            .await
+            .map_err(|_| Error::LoopNotRunning)?;
+
+        self.complete_dispatch(command, packet_slot, permit).await
+    }
+
+    pub async fn try_send_raw_command(
+        &self,
+        command: CommandRequestPacket,
+    ) -> Result<CommandResponsePacket> {
+        let request_id = command.request_id;
+        Self::with_timeout(
+            request_id,
+            self.command_timeout,
+            self.try_dispatch_command(command),
+        )
+        .await
+    }
+
+    async fn try_dispatch_command(
+        &self,
+        command: CommandRequestPacket,
+    ) -> Result<CommandResponsePacket> {
+        self.assert_running()?;
+        let permit = self
+            .command_semaphore
+            .try_acquire()
+            .map_err(|err| match err {
+                TryAcquireError::NoPermits => Error::WouldBlock,
+                TryAcquireError::Closed => Error::ServerShuttingDown,
+            })?;
+        let packet_slot = self.packet_sender.try_reserve().map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => Error::WouldBlock,
+            mpsc::error::TrySendError::Closed(_) => Error::LoopNotRunning,
+        })?;
+
+        // Taken last, since a token can't be handed back if something else turns the
+        // command away; the permit and slot simply drop.
+        if let Some(rate_limiter) = &self.rate_limiter {
+            if !rate_limiter.try_acquire() {
+                return Err(Error::WouldBlock);
+            }
+        }
+
+        self.complete_dispatch(command, packet_slot, permit).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(request_id = %command.request_id, command_line = %command.command_line),
+        )
+    )]
+    async fn complete_dispatch(
+        &self,
+        command: CommandRequestPacket,
+        packet_slot: mpsc::Permit<'_, Packet>,
+        permit: SemaphorePermit<'_>,
+    ) -> Result<CommandResponsePacket> {
+        // The waiter is registered before the request goes out, so a response can never
+        // arrive ahead of it and be reported as unexpected.
+        let (tx, rx) = oneshot::channel();
+        self.command_sender.send((command.request_id, tx)).await?;
+        let mut guard = ForgetGuard::new(&self.control_sender, command.request_id);
+        packet_slot.send(Packet::CommandRequest(command));
+
+        let result = rx.await;
+        // Answered or dropped by the loop, so there's nothing left to forget.
+        guard.disarm();
+        drop(permit);
+        result?
+    }
+
+    pub async fn send_commands(
+        &self,
+        commands: Vec<CommandRequestPacket>,
+    ) -> Vec<Result<CommandResponsePacket>> {
+        // join_all yields results in input order; the semaphore still bounds how many are in flight.
+        join_all(
+            commands
+                .into_iter()
+                .map(|command| self.send_raw_command(command)),
+        )
+        .await
+    }
+
+    pub async fn send_command_nowait<T: Into<CommandRequestPacket>>(&self, request: T) -> Result<()> {
+        self.assert_running()?;
+        self.wait_for_rate_limit().await;
+
+        // Nothing waits for the response, so the loop passes it on as an untracked one.
+        self.packet_sender
+            .send(Packet::CommandRequest(request.into()))
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn send_command<T: Command>(&self, request: T) -> Result<T::Response>
+    where
+        T::Response: TryFrom<CommandResponsePacket, Error = Error>,
+    {
+        let response = self.send_raw_command(request.into()).await?;
+        Self::parse_response::<T>(response)
+    }
+
+    pub async fn send_command_with_timeout<T: Command>(
+        &self,
+        request: T,
+        timeout: Option<Duration>,
+    ) -> Result<T::Response>
+    where
+        T::Response: TryFrom<CommandResponsePacket, Error = Error>,
+    {
+        let response = self
+            .send_raw_command_with_timeout(request.into(), timeout)
+            .await?;
+        Self::parse_response::<T>(response)
+    }
+
+    // Fails with `Error::WouldBlock` instead of waiting when every command permit is taken.
+    pub async fn try_send_command<T: Command>(&self, request: T) -> Result<T::Response>
+    where
+        T::Response: TryFrom<CommandResponsePacket, Error = Error>,
+    {
+        let response = self.try_send_raw_command(request.into()).await?;
+        Self::parse_response::<T>(response)
+    }
+
+    fn parse_response<T: Command>(response: CommandResponsePacket) -> Result<T::Response>
+    where
+        T::Response: TryFrom<CommandResponsePacket, Error = Error>,
+    {
+        match response.as_error() {
+            Some(error) if !T::is_expected_failure(&response) => Err(error),
+            _ => response.try_into(),
+        }
+    }
+
+    pub async fn subscribe<T: Event>(&self) -> Result<EventListener<T>> {
+        self.assert_running()?;
+
+        let event_name = T::get_type();
+        // Taken before subscribing so an event sent straight back can't be missed.
+        let event_receiver = self.event_receiver.resubscribe();
+        // Reserved up front so the first subscriber can queue the packet without letting go
+        // of the lock; otherwise a concurrent release could slip its unsubscribe in first.
+        let packet_slot = self
+            .packet_sender
+            .reserve()
+            .await
+            .map_err(|_| Error::LoopNotRunning)?;
+
+        {
+            let mut subscribed_events = self.subscribed_events.lock().unwrap();
+            let ref_count = subscribed_events.entry(event_name).or_insert(0);
+            *ref_count += 1;
+
+            if *ref_count == 1 {
+                trace::debug!(?event_name, "subscribing");
+                packet_slot.send(Packet::Subscribe(SubscribePacket { event_name }));
+            }
+        }
+
+        Ok(EventListener::new_unchecked(
+            self.subscribed_events.clone(),
+            self.packet_sender.clone(),
+            BroadcastStream::new(event_receiver),
+            self.listener_idle_timeout,
+        ))
+    }
+
+    pub fn is_subscribed(&self, event: EventType) -> bool {
+        self.subscribed_events
+            .lock()
+            .unwrap()
+            .contains_key(&event)
+    }
+
+    pub async fn event_stream<T: Event>(&self) -> Result<impl Stream<Item = Result<T>>> {
+        Ok(self.subscribe::<T>().await?.into_stream())
+    }
+
+    // The listener is dropped on return, which unsubscribes if it was the last one.
+    pub async fn recv_event_once<T: Event>(&self) -> Result<T> {
+        self.subscribe::<T>().await?.recv().await
+    }
+
+    pub fn available_command_permits(&self) -> usize {
+        self.command_semaphore.available_permits()
+    }
+
+    pub fn add_command_permits(&self, permits: usize) {
+        self.command_semaphore.add_permits(permits);
+    }
+
+    // Only permits that are free right now can be taken away, so this returns how many
+    // were; commands already in flight keep theirs.
+    pub fn forget_permits(&self, permits: usize) -> usize {
+        let mut forgotten = 0;
+        while forgotten < permits {
+            match self.command_semaphore.try_acquire() {
+                Ok(permit) => permit.forget(),
+                Err(_) => break,
+            }
+            forgotten += 1;
+        }
+        forgotten
+    }
+
+    pub(crate) fn close(&self) {
+        self.command_semaphore.close();
+        self.subscribed_events.lock().unwrap().clear();
+    }
+}
+
+// Tells the loop to drop a waiter whose caller stopped waiting, e.g. a cancelled or timed
+// out `send_command`. If the control channel is full the entry is left for the next prune.
+struct ForgetGuard<'a> {
+    control_sender: &'a mpsc::Sender<Control>,
+    request_id: Uuid,
+    armed: bool,
+}
+
+impl<'a> ForgetGuard<'a> {
+    fn new(control_sender: &'a mpsc::Sender<Control>, request_id: Uuid) -> Self {
+        Self {
+            control_sender,
+            request_id,
+            armed: true,
+        }
+    }
+
+    fn disarm(&mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for ForgetGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.control_sender
+                .try_send(Control::ForgetCommand(self.request_id))
+                .ok();
+        }
+    }
+}
+
+impl Clone for ServerHandle {
+    fn clone(&self) -> Self {
+        Self {
+            event_receiver: self.event_receiver.resubscribe(),
+            command_sender: self.command_sender.clone(),
+            packet_sender: self.packet_sender.clone(),
+            unexpected_sender: self.unexpected_sender.clone(),
+            control_sender: self.control_sender.clone(),
+            subscribed_events: self.subscribed_events.clone(),
+            command_semaphore: self.command_semaphore.clone(),
+            command_timeout: self.command_timeout,
+            listener_idle_timeout: self.listener_idle_timeout,
+            rate_limiter: self.rate_limiter.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::{OpCommand, SayCommand},
+        event::{EventType, PlayerMessage},
+        packet::{Packet, SubscribePacket},
+        test_util::{connect, connect_with_config, event, request_id, response},
+        Error, ServerConfig,
+    };
+    use futures::{future::join_all, StreamExt};
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn command_gets_its_response() {
+        let (server, mut client) = connect().await;
+
+        let (response, request) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+
+        assert_eq!(request["body"]["commandLine"], "say hello");
+        assert_eq!(response.unwrap().message.as_deref(), Some("hello"));
+    }
+
+    #[tokio::test]
+    async fn expected_failures_are_parsed() {
+        let (server, mut client) = connect().await;
+
+        let answer = async {
+            for message in [
+                "Could not op (already op or higher): Steve",
+                "No targets matched selector",
+            ] {
+                let request = client.recv().await;
+                let body = json!({ "statusMessage": message });
+                client
+                    .send(response(request_id(&request), -2147483648, body))
+                    .await;
+            }
+        };
+        let send = async {
+            let unchanged = server.send_command(OpCommand::new("Steve")).await.unwrap();
+            assert!(!unchanged.changed);
+
+            let missing = server.send_command(OpCommand::new("Alex")).await;
+            assert!(matches!(missing, Err(Error::MinecraftError { .. })));
+        };
+        tokio::join!(answer, send);
+    }
+
+    #[tokio::test]
+    async fn nowait_responses_are_untracked() {
+        let (server, mut client) = connect().await;
+        let mut unexpected = server.unexpected_packets();
+
+        for _ in 0..100 {
+            server
+                .send_command_nowait(SayCommand::new("spam"))
+                .await
+                .unwrap();
+            client.answer_command(json!({})).await;
+            assert!(matches!(
+                unexpected.recv().await,
+                Ok(Packet::CommandResponse(_))
+            ));
+        }
+
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+        assert_eq!(response.unwrap().message.as_deref(), Some("hello"));
+    }
+
+    #[tokio::test]
+    async fn commands_waiting_for_a_permit_leave_the_channel_free() {
+        let config = ServerConfig::default()
+            .with_max_in_flight_commands(1)
+            .with_packet_channel_size(1);
+        let (server, mut client) = connect_with_config(config).await;
+
+        let client_side = async {
+            let first = client.recv().await;
+            // The second command is now waiting for the only permit.
+            let subscribe =
+                tokio::time::timeout(Duration::from_secs(1), server.subscribe::<PlayerMessage>());
+            let _listener = subscribe.await.expect("subscribe was stalled").unwrap();
+            assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+
+            client
+                .send(response(request_id(&first), 0, json!({})))
+                .await;
+            client.answer_command(json!({})).await;
+        };
+        let (first, second, _) = tokio::join!(
+            server.send_command(SayCommand::new("first")),
+            server.send_command(SayCommand::new("second")),
+            client_side,
+        );
+        assert!(first.is_ok() && second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn batched_results_keep_their_input_order() {
+        let (server, mut client) = connect().await;
+        let commands = ["first", "second", "third"]
+            .map(|message| SayCommand::new(message).into())
+            .to_vec();
+
+        let client_side = async {
+            let mut requests = vec![];
+            for _ in 0..3 {
+                requests.push(client.recv().await);
+            }
+            // Answered back to front, so only the input order can line them up.
+            for request in requests.iter().rev() {
+                let message = request["body"]["commandLine"].as_str().unwrap();
+                client
+                    .send(response(
+                        request_id(request),
+                        0,
+                        json!({ "statusMessage": message }),
+                    ))
+                    .await;
+            }
+        };
+        let (results, _) = tokio::join!(server.send_commands(commands), client_side);
+
+        let messages: Vec<_> = results
+            .into_iter()
+            .map(|result| result.unwrap().status_message.unwrap())
+            .collect();
+        assert_eq!(messages, ["say first", "say second", "say third"]);
+    }
+
+    #[tokio::test]
+    async fn tries_fail_fast_while_saturated() {
+        let config = ServerConfig::default().with_max_in_flight_commands(1);
+        let (server, mut client) = connect_with_config(config).await;
+
+        let client_side = async {
+            let first = client.recv().await;
+            // The only permit is held by the first command until this answer goes out.
+            let saturated = tokio::time::timeout(
+                Duration::from_millis(100),
+                server.try_send_command(SayCommand::new("second")),
+            )
+            .await
+            .expect("the try waited for a permit");
+            assert!(matches!(saturated, Err(Error::WouldBlock)));
+            assert_eq!(server.available_command_permits(), 0);
+
+            client
+                .send(response(request_id(&first), 0, json!({})))
+                .await;
+        };
+        let (first, _) = tokio::join!(
+            server.try_send_command(SayCommand::new("first")),
+            client_side,
+        );
+        assert!(first.is_ok());
+
+        let (third, _) = tokio::join!(
+            server.try_send_command(SayCommand::new("third")),
+            client.answer_command(json!({})),
+        );
+        assert!(third.is_ok());
+    }
+
+    #[tokio::test]
+    async fn cloned_handles_send_from_separate_tasks() {
+        let (server, mut client) = connect().await;
+
+        let tasks: Vec<_> = ["left", "right"]
+            .into_iter()
+            .map(|message| {
+                let handle = server.handle().clone();
+                tokio::spawn(async move { handle.send_command(SayCommand::new(message)).await })
+            })
+            .collect();
+        for _ in 0..2 {
+            let request = client.recv().await;
+            let message = request["body"]["commandLine"].as_str().unwrap();
+            client
+                .send(response(
+                    request_id(&request),
+                    0,
+                    json!({ "message": message }),
+                ))
+                .await;
+        }
+
+        let mut messages = vec![];
+        for task in tasks {
+            messages.push(task.await.unwrap().unwrap().message.unwrap());
+        }
+        assert_eq!(messages, ["say left", "say right"]);
+    }
+
+    #[tokio::test]
+    async fn listener_receives_events() {
+        let (server, mut client) = connect().await;
+
+        let mut listener = server.subscribe::<PlayerMessage>().await.unwrap();
+        client.recv().await;
+        client
+            .send(event(
+                "PlayerMessage",
+                json!({ "message": "hi", "sender": "Steve", "receiver": "", "type": "chat" }),
+            ))
+            .await;
+
+        assert_eq!(listener.recv().await.unwrap().message, "hi");
+    }
+
+    #[tokio::test]
+    async fn event_stream_keeps_its_subscription() {
+        let (server, mut client) = connect().await;
+
+        let stream = server.event_stream::<PlayerMessage>().await.unwrap();
+        assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+        for message in ["one", "two", "three"] {
+            let body =
+                json!({ "message": message, "sender": "Steve", "receiver": "", "type": "chat" });
+            client.send(event("PlayerMessage", body)).await;
+        }
+
+        let messages: Vec<String> = stream
+            .take(3)
+            .map(|event| event.unwrap().message)
+            .collect()
+            .await;
+        assert_eq!(messages, ["one", "two", "three"]);
+        assert_eq!(
+            client.recv().await["header"]["messagePurpose"],
+            "unsubscribe"
+        );
+    }
+
+    #[tokio::test]
+    async fn last_listener_unsubscribes() {
+        let (server, mut client) = connect().await;
+
+        let first = server.subscribe::<PlayerMessage>().await.unwrap();
+        assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+        let second = server.subscribe::<PlayerMessage>().await.unwrap();
+
+        drop(first);
+        assert!(server.is_subscribed(EventType::PlayerMessage));
+        assert!(client.is_quiet_for(Duration::from_millis(50)).await);
+
+        drop(second);
+        assert!(!server.is_subscribed(EventType::PlayerMessage));
+        assert_eq!(
+            client.recv().await["header"]["messagePurpose"],
+            "unsubscribe"
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_subscribes_share_one_subscription() {
+        let (server, mut client) = connect().await;
+
+        for _ in 0..10 {
+            let listeners = join_all((0..8).map(|_| server.subscribe::<PlayerMessage>())).await;
+            assert!(server.is_subscribed(EventType::PlayerMessage));
+            drop(listeners);
+            assert!(!server.is_subscribed(EventType::PlayerMessage));
+
+            assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+            assert_eq!(
+                client.recv().await["header"]["messagePurpose"],
+                "unsubscribe"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_tries_keep_their_rate_limit_token() {
+        let config = ServerConfig::default()
+            .with_max_in_flight_commands(1)
+            .with_commands_per_second(Some(2));
+        let (server, mut client) = connect_with_config(config).await;
+
+        let client_side = async {
+            let first = client.recv().await;
+            // The only permit is taken, so this is turned away before the rate limit.
+            let blocked = server.try_send_command(SayCommand::new("blocked")).await;
+            assert!(matches!(blocked, Err(Error::WouldBlock)));
+            client
+                .send(response(request_id(&first), 0, json!({})))
+                .await;
+        };
+        let (first, _) = tokio::join!(
+            server.try_send_command(SayCommand::new("first")),
+            client_side,
+        );
+        assert!(first.is_ok());
+
+        let (second, _) = tokio::join!(
+            server.try_send_command(SayCommand::new("second")),
+            client.answer_command(json!({})),
+        );
+        assert!(second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn listeners_dropped_after_the_loop_stops_leave_no_subscription() {
+        let (server, mut client) = connect().await;
+        let listener = server.subscribe::<PlayerMessage>().await.unwrap();
+        assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+
+        drop(client);
+        while server.is_running() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // The unsubscribe can't go out any more, but the count still drops.
+        drop(listener);
+        assert!(!server.is_subscribed(EventType::PlayerMessage));
+
+        let resubscribed = server.subscribe::<PlayerMessage>().await;
+        assert!(matches!(resubscribed, Err(Error::LoopNotRunning)));
+        assert!(!server.is_subscribed(EventType::PlayerMessage));
+    }
+
+    #[tokio::test]
+    async fn resubscribing_sends_the_subscribe_again() {
+        let (server, mut client) = connect().await;
+
+        for _ in 0..2 {
+            let listener = server.subscribe::<PlayerMessage>().await.unwrap();
+            assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+            drop(listener);
+            assert_eq!(
+                client.recv().await["header"]["messagePurpose"],
+                "unsubscribe"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn the_configured_timeout_applies_by_default() {
+        let config = ServerConfig::default().with_command_timeout(Some(Duration::from_millis(50)));
+        let (server, mut client) = connect_with_config(config).await;
+
+        let (result, request) = tokio::join!(
+            server.send_command(SayCommand::new("unanswered")),
+            client.recv(),
+        );
+        assert!(matches!(
+            result,
+            Err(Error::CommandTimedOut(id)) if id.to_string() == request_id(&request)
+        ));
+    }
+
+    #[tokio::test]
+    async fn per_call_timeouts_override_the_default() {
+        let config = ServerConfig::default().with_command_timeout(Some(Duration::from_millis(50)));
+        let (server, mut client) = connect_with_config(config).await;
+
+        let client_side = async {
+            let request = client.recv().await;
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            client
+                .send(response(request_id(&request), 0, json!({})))
+                .await;
+        };
+        let (result, _) = tokio::join!(
+            server.send_command_with_timeout(SayCommand::new("slow"), Some(Duration::from_secs(5))),
+            client_side,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn the_rate_limit_spreads_commands_out() {
+        let config = ServerConfig::default().with_commands_per_second(Some(10));
+        let (server, mut client) = connect_with_config(config).await;
+
+        let client_side = async {
+            for _ in 0..15 {
+                client.answer_command(json!({})).await;
+            }
+        };
+        let started = tokio::time::Instant::now();
+        let commands = (0..15).map(|i| server.send_command(SayCommand::new(&i.to_string())));
+        let (results, _) = tokio::join!(join_all(commands), client_side);
+
+        assert!(results.into_iter().all(|result| result.is_ok()));
+        // The first ten go out as a burst, the last five at ten a second.
+        assert!(started.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[tokio::test]
+    async fn drain_returns_every_buffered_event() {
+        let (mut server, mut client) = connect().await;
+
+        for message in ["one", "two", "three"] {
+            client
+                .send(event("PlayerMessage", json!({ "message": message })))
+                .await;
+        }
+        // Events are handled in order, so all three are buffered once this is answered.
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("sync")),
+            client.answer_command(json!({})),
+        );
+        assert!(response.is_ok());
+
+        let messages: Vec<_> = server
+            .drain_raw_events()
+            .into_iter()
+            .map(|event| event.properties()["message"].clone())
+            .collect();
+        assert_eq!(messages, ["one", "two", "three"]);
+        assert!(server.drain_raw_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn raw_packets_are_written_as_is() {
+        let (server, mut client) = connect().await;
+
+        server
+            .send_raw_packet(Packet::Subscribe(SubscribePacket {
+                event_name: EventType::BlockBroken,
+            }))
+            .await
+            .unwrap();
+
+        let packet = client.recv().await;
+        assert_eq!(packet["header"]["messagePurpose"], "subscribe");
+        assert_eq!(packet["body"]["eventName"], "BlockBroken");
+        // Bypassing the subscription counts leaves them untouched.
+        assert!(!server.is_subscribed(EventType::BlockBroken));
+    }
+
+    #[tokio::test]
+    async fn raw_packets_need_a_running_loop() {
+        let (server, client) = connect().await;
+        drop(client);
+        while server.is_running() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let result = server
+            .send_raw_packet(Packet::Subscribe(SubscribePacket {
+                event_name: EventType::BlockBroken,
+            }))
+            .await;
+        assert!(matches!(result, Err(Error::LoopNotRunning)));
+    }
+
+    #[tokio::test]
+    async fn overrun_listeners_report_the_gap_once() {
+        let config = ServerConfig::default().with_event_channel_size(2);
+        let (server, mut client) = connect_with_config(config).await;
+        let mut listener = server.subscribe::<PlayerMessage>().await.unwrap();
+        client.recv().await;
+
+        for i in 0..5 {
+            let body = json!({ "message": i.to_string(), "type": "chat", "sender": "Steve", "receiver": "" });
+            client.send(event("PlayerMessage", body)).await;
+        }
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("sync")),
+            client.answer_command(json!({})),
+        );
+        assert!(response.is_ok());
+
+        assert!(matches!(listener.recv().await, Err(Error::Lagged(3))));
+        assert_eq!(listener.recv().await.unwrap().message, "3");
+        assert_eq!(listener.recv().await.unwrap().message, "4");
+    }
+
+    #[tokio::test]
+    async fn idle_listeners_unsubscribe_themselves() {
+        let config =
+            ServerConfig::default().with_listener_idle_timeout(Some(Duration::from_millis(50)));
+        let (server, mut client) = connect_with_config(config).await;
+        let mut listener = server.subscribe::<PlayerMessage>().await.unwrap();
+        assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+
+        assert_eq!(
+            client.recv().await["header"]["messagePurpose"],
+            "unsubscribe"
+        );
+        assert!(!server.is_subscribed(EventType::PlayerMessage));
+        assert!(matches!(
+            listener.recv().await,
+            Err(Error::StreamExhausted(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn listeners_waiting_in_recv_are_not_idle() {
+        let config =
+            ServerConfig::default().with_listener_idle_timeout(Some(Duration::from_millis(50)));
+        let (server, mut client) = connect_with_config(config).await;
+        let mut listener = server.subscribe::<PlayerMessage>().await.unwrap();
+        client.recv().await;
+
+        let (waited, quiet) = tokio::join!(
+            tokio::time::timeout(Duration::from_millis(300), listener.recv()),
+            client.is_quiet_for(Duration::from_millis(250)),
+        );
+        assert!(waited.is_err());
+        assert!(quiet);
+        assert!(server.is_subscribed(EventType::PlayerMessage));
+    }
+
+    #[tokio::test]
+    async fn forgetting_permits_serializes_commands() {
+        let config = ServerConfig::default().with_max_in_flight_commands(3);
+        let (server, mut client) = connect_with_config(config).await;
+        assert_eq!(server.forget_permits(2), 2);
+        assert_eq!(server.available_command_permits(), 1);
+
+        let client_side = async {
+            let first = client.recv().await;
+            // The second command has to wait for the only permit left.
+            assert!(client.is_quiet_for(Duration::from_millis(100)).await);
+            client
+                .send(response(request_id(&first), 0, json!({})))
+                .await;
+            client.answer_command(json!({})).await;
+        };
+        let (first, second, _) = tokio::join!(
+            server.send_command(SayCommand::new("first")),
+            server.send_command(SayCommand::new("second")),
+            client_side,
+        );
+        assert!(first.is_ok() && second.is_ok());
+
+        server.add_command_permits(2);
+        assert_eq!(server.available_command_permits(), 3);
+    }
+
+    #[tokio::test]
+    async fn only_free_permits_are_forgotten() {
+        let config = ServerConfig::default().with_max_in_flight_commands(3);
+        let (server, _client) = connect_with_config(config).await;
+
+        assert_eq!(server.forget_permits(10), 3);
+        assert_eq!(server.available_command_permits(), 0);
+    }
+}
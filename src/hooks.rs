@@ -0,0 +1,57 @@
+use crate::{packet::Packet, Error};
+use std::{fmt, sync::Arc};
+
+pub type PacketHook = Arc<dyn Fn(&Packet) + Send + Sync>;
+pub type ErrorHook = Arc<dyn Fn(&Error) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct Hooks {
+    pub on_inbound: Option<PacketHook>,
+    pub on_outbound: Option<PacketHook>,
+    pub on_error: Option<ErrorHook>,
+}
+
+impl Hooks {
+    pub fn with_inbound<F: Fn(&Packet) + Send + Sync + 'static>(mut self, hook: F) -> Self {
+        self.on_inbound = Some(Arc::new(hook));
+        self
+    }
+
+    pub fn with_outbound<F: Fn(&Packet) + Send + Sync + 'static>(mut self, hook: F) -> Self {
+        self.on_outbound = Some(Arc::new(hook));
+        self
+    }
+
+    pub fn with_error<F: Fn(&Error) + Send + Sync + 'static>(mut self, hook: F) -> Self {
+        self.on_error = Some(Arc::new(hook));
+        self
+    }
+
+    pub(crate) fn inbound(&self, packet: &Packet) {
+        if let Some(hook) = &self.on_inbound {
+            hook(packet);
+        }
+    }
+
+    pub(crate) fn outbound(&self, packet: &Packet) {
+        if let Some(hook) = &self.on_outbound {
+            hook(packet);
+        }
+    }
+
+    pub(crate) fn error(&self, error: &Error) {
+        if let Some(hook) = &self.on_error {
+            hook(error);
+        }
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_inbound", &self.on_inbound.is_some())
+            .field("on_outbound", &self.on_outbound.is_some())
+            .field("on_error", &self.on_error.is_some())
+            .finish()
+    }
+}
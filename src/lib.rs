@@ -1,8 +1,33 @@
+
+mod backoff;
 pub mod command;
+mod config;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 mod error;
 pub mod event;
+mod handle;
+mod hooks;
+mod listener;
 pub mod packet;
+mod queue;
+mod rate_limit;
+mod reconnect;
+mod recording;
+mod router;
 mod server;
+#[cfg(test)]
+mod test_util;
+mod trace;
+pub mod ws;
 
+pub use backoff::BackoffPolicy;
+pub use config::ServerConfig;
 pub use error::*;
-pub use server::Server;
+pub use handle::ServerHandle;
+pub use hooks::{ErrorHook, Hooks, PacketHook};
+pub use listener::WsListener;
+pub use queue::CommandQueue;
+pub use reconnect::{DurableEventListener, ReconnectingServer};
+pub use router::{ChatCommand, ChatHandler, ChatRouter};
+pub use server::{ChatTarget, CloseInfo, ConnectionInfo, Server};
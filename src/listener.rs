@@ -0,0 +1,84 @@
+use crate::{Result, Server};
+use futures::{stream, Stream};
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+pub struct WsListener {
+    listener: TcpListener,
+}
+
+impl WsListener {
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub async fn accept(&self) -> Result<Server> {
+        let (stream, peer_addr) = self.listener.accept().await?;
+        Server::accept(stream, Some(peer_addr)).await
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<Server>> {
+        stream::unfold(self, |listener| async {
+            let server = listener.accept().await;
+            Some((server, listener))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+    // The client's websocket, to keep the connection open, and its address.
+    async fn connect_client(
+        addr: SocketAddr,
+    ) -> (WebSocketStream<MaybeTlsStream<TcpStream>>, SocketAddr) {
+        let (websocket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        let client_addr = match websocket.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.local_addr().unwrap(),
+            _ => unreachable!(),
+        };
+        (websocket, client_addr)
+    }
+
+    #[tokio::test]
+    async fn accept_yields_a_server_per_connection() {
+        let listener = WsListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (server, (_websocket, client_addr)) =
+            tokio::join!(listener.accept(), connect_client(addr));
+        let server = server.unwrap();
+        assert!(server.is_running());
+        assert_eq!(server.connection_info().peer_addr, Some(client_addr));
+    }
+
+    #[tokio::test]
+    async fn the_stream_keeps_accepting() {
+        let listener = WsListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let servers = listener.into_stream().take(2).collect::<Vec<_>>();
+
+        let (servers, first, second) =
+            tokio::join!(servers, connect_client(addr), connect_client(addr));
+        let mut peers: Vec<_> = servers
+            .into_iter()
+            .map(|server| server.unwrap().connection_info().peer_addr.unwrap())
+            .collect();
+        let mut clients = vec![first.1, second.1];
+        peers.sort();
+        clients.sort();
+        assert_eq!(peers, clients);
+    }
+}
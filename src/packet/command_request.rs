@@ -1,14 +1,15 @@
-use super::serialize_packet;
+use super::{deserialize_packet, serialize_packet};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum OriginType {
     Player,
+    AutomationPlayer,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Origin {
     #[serde(rename = "origin")]
     pub origin_type: OriginType,
@@ -22,7 +23,7 @@ impl Default for Origin {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct CommandRequestPacket {
     pub origin: Origin,
     pub command_line: String,
@@ -37,8 +38,51 @@ impl CommandRequestPacket {
             request_id: Uuid::new_v4(),
         }
     }
+
+    pub fn builder() -> CommandRequestPacketBuilder {
+        CommandRequestPacketBuilder::default()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CommandRequestPacketBuilder {
+    origin: Origin,
+    command_line: String,
+    request_id: Option<Uuid>,
+}
+
+impl CommandRequestPacketBuilder {
+    pub fn command_line(mut self, command_line: &str) -> Self {
+        self.command_line = command_line.to_string();
+        self
+    }
+
+    pub fn origin(mut self, origin_type: OriginType) -> Self {
+        self.origin = Origin { origin_type };
+        self
+    }
+
+    pub fn request_id(mut self, request_id: Uuid) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    pub fn build(self) -> CommandRequestPacket {
+        CommandRequestPacket {
+            origin: self.origin,
+            command_line: self.command_line,
+            request_id: self.request_id.unwrap_or_else(Uuid::new_v4),
+        }
+    }
 }
 
+deserialize_packet!(
+    CommandRequestPacket; "commandRequest",
+    body "origin" => Origin: origin,
+    body "commandLine" => String: command_line,
+    header "requestId" => Uuid: request_id,
+);
+
 serialize_packet!(
     CommandRequestPacket; "commandRequest",
     body "origin" => Origin: origin,
@@ -47,3 +91,45 @@ serialize_packet!(
     header "requestId" => Uuid: request_id,
     header "version" => i32: 1,
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn builder_sets_a_custom_origin() {
+        let request_id = Uuid::new_v4();
+        let packet = CommandRequestPacket::builder()
+            .command_line("say hi")
+            .origin(OriginType::AutomationPlayer)
+            .request_id(request_id)
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(packet).unwrap(),
+            json!({
+                "header": {
+                    "messagePurpose": "commandRequest",
+                    "messageType": "commandRequest",
+                    "requestId": request_id,
+                    "version": 1,
+                },
+                "body": {
+                    "origin": { "origin": "automationPlayer" },
+                    "commandLine": "say hi",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn builder_defaults_to_a_player_and_a_fresh_id() {
+        let first = CommandRequestPacket::builder().command_line("list").build();
+        let second = CommandRequestPacket::builder().command_line("list").build();
+
+        assert_eq!(first.origin.origin_type, OriginType::Player);
+        assert_eq!(first.command_line, "list");
+        assert_ne!(first.request_id, second.request_id);
+    }
+}
@@ -1,12 +1,72 @@
-use super::{deserialize_packet, JsonObject};
+use super::{deserialize_packet, serialize_packet, JsonObject};
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use uuid::Uuid;
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandResponsePacket {
     pub status_code: i32,
     pub status_message: Option<String>,
     pub request_id: Uuid,
     pub extra_data: JsonObject,
+    // As sent by the peer, and written back out when the packet is serialized.
+    pub protocol_version: i64,
+    // Header entries besides the purpose, version and request id.
+    pub extra_header: JsonObject,
+}
+
+// Version 1 is the only one this crate speaks, so packets built locally claim it.
+impl Default for CommandResponsePacket {
+    fn default() -> Self {
+        Self {
+            status_code: 0,
+            status_message: None,
+            request_id: Uuid::nil(),
+            extra_data: JsonObject::new(),
+            protocol_version: 1,
+            extra_header: JsonObject::new(),
+        }
+    }
+}
+
+impl CommandResponsePacket {
+    // Failures can come back as a normal response carrying a nonzero status code.
+    pub fn is_error(&self) -> bool {
+        self.status_code != 0
+    }
+
+    pub fn as_error(&self) -> Option<Error> {
+        self.is_error().then(|| Error::MinecraftError {
+            status_message: self.status_message.clone(),
+            status_code: self.status_code,
+        })
+    }
+
+    pub fn get_field<T: DeserializeOwned>(&self, key: &'static str) -> Result<T> {
+        let value = self
+            .extra_data
+            .get(key)
+            .ok_or(Error::MissingField(key))?;
+        serde_json::from_value(value.clone()).map_err(|_| Error::InvalidType)
+    }
+
+    // Walks `extra_data` by a dot-separated path such as "details.players.0.name", where
+    // numeric segments index into arrays.
+    pub fn get_path(&self, path: &str) -> Option<&Value> {
+        let mut segments = path.split('.');
+        let mut value = self.extra_data.get(segments.next()?)?;
+
+        for segment in segments {
+            value = match value {
+                Value::Object(map) => map.get(segment)?,
+                Value::Array(array) => array.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
 }
 
 deserialize_packet!(
@@ -14,5 +74,89 @@ deserialize_packet!(
     body "statusCode" => i32: status_code,
     body "statusMessage" => Option<String>: status_message,
     header "requestId" => Uuid: request_id,
+    header "version" => i64: protocol_version,
+    other_header => extra_header,
     other_body => extra_data,
 );
+
+serialize_packet!(
+    CommandResponsePacket; "commandResponse",
+    body "statusCode" => i32: status_code,
+    body "statusMessage" => Option<String>: status_message,
+    header "requestId" => Uuid: request_id,
+    header "version" => i64: protocol_version,
+    other_header => extra_header,
+    other_body => extra_data,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{packet::Packet, test_util::response};
+    use serde_json::json;
+
+    fn parse(packet: Value) -> CommandResponsePacket {
+        match Packet::from_json(&packet.to_string()).unwrap() {
+            Packet::CommandResponse(response) => response,
+            other => panic!("expected a command response, got {other}"),
+        }
+    }
+
+    #[test]
+    fn nonzero_statuses_are_errors() {
+        let packet = parse(response(
+            "00000000-0000-0000-0000-000000000000",
+            -2147483648,
+            json!({ "statusMessage": "Syntax error" }),
+        ));
+
+        assert!(packet.is_error());
+        assert!(matches!(
+            packet.as_error(),
+            Some(Error::MinecraftError { status_code: -2147483648, status_message: Some(message) })
+                if message == "Syntax error"
+        ));
+    }
+
+    #[test]
+    fn zero_statuses_are_not_errors() {
+        let packet = parse(response(
+            "00000000-0000-0000-0000-000000000000",
+            0,
+            json!({}),
+        ));
+        assert!(!packet.is_error());
+        assert!(packet.as_error().is_none());
+    }
+
+    #[test]
+    fn paths_walk_objects_and_arrays() {
+        let packet = parse(response(
+            "00000000-0000-0000-0000-000000000000",
+            0,
+            json!({ "details": { "players": [{ "name": "Steve" }, { "name": "Alex" }] } }),
+        ));
+
+        assert_eq!(
+            packet.get_path("details.players.1.name"),
+            Some(&json!("Alex"))
+        );
+        assert_eq!(packet.get_path("details.players.2.name"), None);
+        assert_eq!(packet.get_path("details.players.first"), None);
+        assert_eq!(packet.get_path("details.players.0.name.more"), None);
+        assert_eq!(packet.get_path("missing"), None);
+    }
+
+    #[test]
+    fn custom_headers_are_captured_and_written_back() {
+        let mut packet = response("00000000-0000-0000-0000-000000000000", 0, json!({}));
+        packet["header"]["build"] = json!("1.20.0");
+        let packet = parse(packet);
+
+        assert_eq!(packet.extra_header["build"], "1.20.0");
+        assert!(!packet.extra_header.contains_key("requestId"));
+
+        let json = serde_json::to_value(&packet).unwrap();
+        assert_eq!(json["header"]["build"], "1.20.0");
+    }
+}
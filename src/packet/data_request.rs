@@ -0,0 +1,31 @@
+use super::{deserialize_packet, serialize_packet};
+use uuid::Uuid;
+
+// Asks the game for structured data such as `"block"`, `"item"` or `"mob"`; not every
+// version answers every type.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DataRequestPacket {
+    pub data_type: String,
+    pub request_id: Uuid,
+}
+
+impl DataRequestPacket {
+    pub fn new(data_type: &str) -> Self {
+        Self {
+            data_type: data_type.to_string(),
+            request_id: Uuid::new_v4(),
+        }
+    }
+}
+
+deserialize_packet!(
+    DataRequestPacket; "dataRequest",
+    body "dataType" => String: data_type,
+    header "requestId" => Uuid: request_id,
+);
+
+serialize_packet!(
+    DataRequestPacket; "dataRequest",
+    body "dataType" => String: data_type,
+    header "requestId" => Uuid: request_id,
+);
@@ -0,0 +1,24 @@
+use super::{deserialize_packet, serialize_packet, JsonObject};
+use uuid::Uuid;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DataResponsePacket {
+    pub request_id: Uuid,
+    pub data: JsonObject,
+    // Header entries besides the purpose, version and request id.
+    pub extra_header: JsonObject,
+}
+
+deserialize_packet!(
+    DataResponsePacket; "data",
+    header "requestId" => Uuid: request_id,
+    other_header => extra_header,
+    other_body => data,
+);
+
+serialize_packet!(
+    DataResponsePacket; "data",
+    header "requestId" => Uuid: request_id,
+    other_header => extra_header,
+    other_body => data,
+);
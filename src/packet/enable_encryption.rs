@@ -0,0 +1,110 @@
+use super::{CommandRequestPacket, CommandResponsePacket};
+use crate::{command::escape::quote, Error, Result};
+use serde::{Serialize, Serializer};
+use uuid::Uuid;
+
+// The handshake travels as an ordinary command request, so this serializes as one.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EnableEncryptionRequest {
+    pub public_key: String,
+    pub salt: String,
+    pub request_id: Uuid,
+}
+
+impl EnableEncryptionRequest {
+    pub fn new(public_key: &str, salt: &str) -> Self {
+        Self {
+            public_key: public_key.to_string(),
+            salt: salt.to_string(),
+            request_id: Uuid::new_v4(),
+        }
+    }
+}
+
+impl From<EnableEncryptionRequest> for CommandRequestPacket {
+    fn from(value: EnableEncryptionRequest) -> Self {
+        CommandRequestPacket::builder()
+            .command_line(&format!(
+                "enableencryption {} {}",
+                quote(&value.public_key),
+                quote(&value.salt),
+            ))
+            .request_id(value.request_id)
+            .build()
+    }
+}
+
+impl Serialize for EnableEncryptionRequest {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        CommandRequestPacket::from(self.clone()).serialize(serializer)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EnableEncryptionResponse {
+    pub public_key: String,
+    pub request_id: Uuid,
+}
+
+impl TryFrom<&CommandResponsePacket> for EnableEncryptionResponse {
+    type Error = Error;
+
+    fn try_from(value: &CommandResponsePacket) -> Result<Self> {
+        if let Some(error) = value.as_error() {
+            return Err(error);
+        }
+
+        Ok(Self {
+            public_key: value.get_field("publicKey")?,
+            request_id: value.request_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::status_reply;
+    use serde_json::json;
+
+    #[test]
+    fn requests_serialize_as_commands() {
+        let request = EnableEncryptionRequest::new("MHYw+key=", "c2FsdA==");
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["header"]["messagePurpose"], "commandRequest");
+        assert_eq!(json["header"]["requestId"], request.request_id.to_string());
+        assert_eq!(
+            json["body"]["commandLine"],
+            r#"enableencryption "MHYw+key=" "c2FsdA==""#
+        );
+    }
+
+    #[test]
+    fn responses_carry_the_peer_key() {
+        let mut response = status_reply(0, "");
+        response.request_id = Uuid::from_u128(7);
+        response
+            .extra_data
+            .insert("publicKey".to_string(), json!("MHYwEAYH"));
+
+        let response = EnableEncryptionResponse::try_from(&response).unwrap();
+        assert_eq!(response.public_key, "MHYwEAYH");
+        assert_eq!(response.request_id, Uuid::from_u128(7));
+    }
+
+    #[test]
+    fn failed_responses_are_errors() {
+        let response = status_reply(-1, "Encryption is not supported");
+        assert!(matches!(
+            EnableEncryptionResponse::try_from(&response),
+            Err(Error::MinecraftError {
+                status_code: -1,
+                ..
+            })
+        ));
+    }
+}
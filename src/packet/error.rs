@@ -1,13 +1,27 @@
-use super::deserialize_packet;
+use super::{deserialize_packet, serialize_packet, JsonObject};
+use uuid::Uuid;
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ErrorPacket {
     pub status_message: String,
     pub status_code: i32,
+    pub request_id: Option<Uuid>,
+    // Header entries besides the purpose, version and request id.
+    pub extra_header: JsonObject,
 }
 
 deserialize_packet!(
     ErrorPacket; "error",
     body "statusMessage" => String: status_message,
     body "statusCode" => i32: status_code,
+    header "requestId" => Option<Uuid>: request_id,
+    other_header => extra_header,
+);
+
+serialize_packet!(
+    ErrorPacket; "error",
+    body "statusMessage" => String: status_message,
+    body "statusCode" => i32: status_code,
+    header "requestId" => Option<Uuid>: request_id,
+    other_header => extra_header,
 );
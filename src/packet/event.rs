@@ -1,14 +1,102 @@
-use super::{deserialize_packet, JsonObject};
-use crate::event::EventType;
+use super::{deserialize_packet, serialize_packet, JsonObject};
+use crate::{
+    event::{Event, EventType},
+    Error, Result,
+};
+use serde_json::Value;
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EventPacket {
     pub event_name: EventType,
     pub(crate) properties: JsonObject,
+    // As sent by the peer, and written back out when the packet is serialized.
+    pub protocol_version: i64,
+    // Header entries besides the purpose, version and event name.
+    pub extra_header: JsonObject,
+}
+
+// Version 1 is the only one this crate speaks, so packets built locally claim it.
+impl Default for EventPacket {
+    fn default() -> Self {
+        Self {
+            event_name: EventType::default(),
+            properties: JsonObject::new(),
+            protocol_version: 1,
+            extra_header: JsonObject::new(),
+        }
+    }
+}
+
+impl EventPacket {
+    pub fn properties(&self) -> &JsonObject {
+        &self.properties
+    }
+
+    pub fn deserialize_as<T: Event>(&self) -> Result<T> {
+        if self.event_name != T::get_type() {
+            return Err(Error::EventTypeMismatch {
+                expected: T::get_type(),
+                actual: self.event_name,
+            });
+        }
+
+        Ok(serde_json::from_value(Value::Object(self.properties.clone()))?)
+    }
 }
 
 deserialize_packet!(
     EventPacket; "event",
     header "eventName" => EventType: event_name,
+    other_header => extra_header,
+    header "version" => i64: protocol_version,
     other_body => properties,
 );
+
+serialize_packet!(
+    EventPacket; "event",
+    header "eventName" => EventType: event_name,
+    header "version" => i64: protocol_version,
+    other_header => extra_header,
+    other_body => properties,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{MobKilled, PlayerMessage},
+        packet::Packet,
+    };
+
+    fn raw_event() -> EventPacket {
+        let json = r#"{
+            "header": { "version": 1, "messagePurpose": "event", "eventName": "PlayerMessage" },
+            "body": { "message": "hi", "type": "chat", "sender": "Steve", "receiver": "" }
+        }"#;
+        match Packet::from_json(json).unwrap() {
+            Packet::Event(event) => event,
+            other => panic!("expected an event, got {other}"),
+        }
+    }
+
+    #[test]
+    fn raw_events_deserialize_as_their_type() {
+        let event = raw_event();
+        assert_eq!(event.properties()["sender"], "Steve");
+
+        let message = event.deserialize_as::<PlayerMessage>().unwrap();
+        assert_eq!(message.message, "hi");
+        assert_eq!(message.sender, "Steve");
+    }
+
+    #[test]
+    fn other_types_are_refused() {
+        assert!(matches!(
+            raw_event().deserialize_as::<MobKilled>(),
+            Err(Error::EventTypeMismatch {
+                expected: EventType::MobKilled,
+                actual: EventType::PlayerMessage,
+            })
+        ));
+    }
+}
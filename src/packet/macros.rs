@@ -29,11 +29,11 @@ pub(super) fn get_unexpected_number<'a>(number: &Number) -> Unexpected<'a> {
         number.as_i64().map_or(
             number.as_f64().map_or(
                 Unexpected::Other("unknown number"),
-                |value| Unexpected::Float(value),
+                Unexpected::Float,
             ),
-            |value| Unexpected::Signed(value),
+            Unexpected::Signed,
         ),
-        |value| Unexpected::Unsigned(value),
+        Unexpected::Unsigned,
     )
 }
 
@@ -58,17 +58,24 @@ macro_rules! select_map {
 }
 
 macro_rules! serialize_packet {
-    (@serialize $self:ident, $header:expr, $body:expr,) => {};
+    (@serialize $self:ident, $header:expr, $body:expr, $omitted:expr,) => {};
     (@extract_value $self:ident $field:ident) => {
         $self.$field.clone()
     };
     (@extract_value $self:ident $const:literal) => {
         $const
     };
-    (@serialize $self:ident, $header:expr, $body:expr, other_body => $field:ident,) => {
+    (@serialize $self:ident, $header:expr, $body:expr, $omitted:expr, other_body => $field:ident,) => {
         $body.append(&mut $self.clone().$field);
     };
-    (@serialize $self:ident, $header:expr, $body:expr, $map_id:ident $key:literal => Option<$type:ty>: $field:ident, $($tail:tt)*) => {
+    // The captured header is written back as it was, so a request id it lacked stays out.
+    (@serialize $self:ident, $header:expr, $body:expr, $omitted:expr, other_header => $field:ident, $($tail:tt)*) => {
+        $header.append(&mut $self.clone().$field);
+        $omitted.push("requestId");
+        serialize_packet!(@serialize $self, $header, $body, $omitted, $($tail)*);
+    };
+    // A `None` leaves its entry out entirely, so the defaults below don't fill it in.
+    (@serialize $self:ident, $header:expr, $body:expr, $omitted:expr, $map_id:ident $key:literal => Option<$type:ty>: $field:ident, $($tail:tt)*) => {
         match $self.$field.clone() {
             Some(value) => {
                 $crate::packet::select_map!($header, $body, $map_id).insert(
@@ -78,18 +85,18 @@ macro_rules! serialize_packet {
                         .ok_or(::serde::ser::Error::custom("failed to serialize field"))?
                 );
             }
-            None => {}
+            None => $omitted.push($key),
         };
-        serialize_packet!(@serialize $self, $header, $body, $($tail)*);
+        serialize_packet!(@serialize $self, $header, $body, $omitted, $($tail)*);
     };
-    (@serialize $self:ident, $header:expr, $body:expr, $map_id:ident $key:literal => $type:ty: $value:tt, $($tail:tt)*) => {
+    (@serialize $self:ident, $header:expr, $body:expr, $omitted:expr, $map_id:ident $key:literal => $type:ty: $value:tt, $($tail:tt)*) => {
         $crate::packet::select_map!($header, $body, $map_id).insert(
             $key.to_string(),
             ::serde_json::to_value(
                 serialize_packet!(@extract_value $self $value)
             ).ok().ok_or(::serde::ser::Error::custom("failed to serialize field"))?
         );
-        serialize_packet!(@serialize $self, $header, $body, $($tail)*);
+        serialize_packet!(@serialize $self, $header, $body, $omitted, $($tail)*);
     };
     ($type:ident; $purpose:expr, $($tail:tt)*) => {
         impl ::serde::ser::Serialize for $type {
@@ -102,12 +109,16 @@ macro_rules! serialize_packet {
                 let mut map = serializer.serialize_map(None)?;
                 let mut header = ::serde_json::Map::new();
                 let mut body = ::serde_json::Map::new();
+                #[allow(unused_mut)]
+                let mut omitted: ::std::vec::Vec<&str> = ::std::vec::Vec::new();
 
-                serialize_packet!(@serialize self, header, body, $($tail)*);
+                serialize_packet!(@serialize self, header, body, omitted, $($tail)*);
 
                 header.insert("messagePurpose".to_string(), ::serde_json::Value::String($purpose.to_string()));
-                header.insert("version".to_string(), ::serde_json::Value::Number(1.into()));
-                if !header.contains_key("requestId") {
+                if !header.contains_key("version") {
+                    header.insert("version".to_string(), ::serde_json::Value::Number(1.into()));
+                }
+                if !header.contains_key("requestId") && !omitted.contains(&"requestId") {
                     header.insert("requestId".to_string(), ::serde_json::Value::String(::uuid::Uuid::new_v4().to_string()));
                 }
 
@@ -140,7 +151,15 @@ macro_rules! deserialize_packet {
             ))?;
     };
     (@deserialize $self:ident, $header:expr, $body:expr, other_body => $field:ident,) => {
-        $self.$field = $body;
+        $self.$field = ::std::mem::take(&mut $body);
+    };
+    // Takes whatever header entries the remaining fields leave behind, minus the two
+    // every packet carries.
+    (@deserialize $self:ident, $header:expr, $body:expr, other_header => $field:ident, $($tail:tt)*) => {
+        deserialize_packet!(@deserialize $self, $header, $body, $($tail)*);
+        $header.remove("messagePurpose");
+        $header.remove("version");
+        $self.$field = ::std::mem::take(&mut $header);
     };
     (@deserialize $self:ident, $header:expr, $body:expr, $map_id:ident $key:literal => Option<$type:ty>: $field:ident, $($tail:tt)*) => {
         $self.$field = match $crate::packet::select_map!($header, $body, $map_id).remove($key) {
@@ -289,3 +308,79 @@ macro_rules! deserialize_packet {
 pub(super) use deserialize_packet;
 pub(super) use select_map;
 pub(super) use serialize_packet;
+
+#[cfg(test)]
+mod tests {
+    use crate::packet::{ErrorPacket, Packet};
+
+    const RESPONSE_WITH_EXTRA_FIELDS: &str = r#"{
+        "header": {
+            "version": 1,
+            "messagePurpose": "commandResponse",
+            "requestId": "00000000-0000-0000-0000-000000000000",
+            "extraHeader": "kept"
+        },
+        "body": { "statusCode": 0, "extra": true }
+    }"#;
+
+    #[test]
+    fn sinks_collect_unknown_fields() {
+        let Packet::CommandResponse(response) =
+            Packet::from_json(RESPONSE_WITH_EXTRA_FIELDS).unwrap()
+        else {
+            panic!("expected a command response");
+        };
+        assert_eq!(response.extra_data["extra"], true);
+        assert_eq!(
+            response.extra_header.keys().collect::<Vec<_>>(),
+            ["extraHeader"]
+        );
+    }
+
+    #[test]
+    fn missing_request_ids_stay_missing() {
+        let packet = Packet::Error(ErrorPacket {
+            status_message: "Syntax error".to_string(),
+            status_code: -1,
+            ..Default::default()
+        });
+        let json = packet.to_json().unwrap();
+
+        assert!(!json.contains("requestId"));
+        assert_eq!(Packet::from_json(&json).unwrap(), packet);
+    }
+
+    #[test]
+    fn header_sinks_leave_out_the_known_entries() {
+        let header = r#""version": 1, "requestId": "00000000-0000-0000-0000-000000000000", "build": "1.20.0""#;
+        let packets = [
+            (
+                format!(
+                    r#"{{"header": {{ {header}, "messagePurpose": "error" }}, "body": {{ "statusCode": -1, "statusMessage": "no" }} }}"#
+                ),
+                vec!["build"],
+            ),
+            (
+                format!(r#"{{"header": {{ {header}, "messagePurpose": "data" }}, "body": {{}} }}"#),
+                vec!["build"],
+            ),
+            // Events have no request id field, so theirs is kept with the rest.
+            (
+                format!(
+                    r#"{{"header": {{ {header}, "messagePurpose": "event", "eventName": "PlayerMessage" }}, "body": {{}} }}"#
+                ),
+                vec!["build", "requestId"],
+            ),
+        ];
+
+        for (json, expected) in packets {
+            let extra_header = match Packet::from_json(&json).unwrap() {
+                Packet::Error(packet) => packet.extra_header,
+                Packet::DataResponse(packet) => packet.extra_header,
+                Packet::Event(packet) => packet.extra_header,
+                other => panic!("unexpected packet {other}"),
+            };
+            assert_eq!(extra_header.keys().collect::<Vec<_>>(), expected);
+        }
+    }
+}
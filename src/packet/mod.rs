@@ -1,21 +1,28 @@
 mod command_request;
 mod command_response;
+mod data_request;
+mod data_response;
+mod enable_encryption;
 mod error;
 mod event;
 mod macros;
+#[allow(clippy::module_inception)]
 mod packet;
 mod subscribe;
 mod unsubscribe;
 
 use serde_json::{Map, Value};
 
-pub use command_request::{CommandRequestPacket, Origin, OriginType};
+pub use command_request::{CommandRequestPacket, CommandRequestPacketBuilder, Origin, OriginType};
 pub use command_response::CommandResponsePacket;
+pub use data_request::DataRequestPacket;
+pub use data_response::DataResponsePacket;
+pub use enable_encryption::{EnableEncryptionRequest, EnableEncryptionResponse};
 pub use error::ErrorPacket;
 pub use event::EventPacket;
-pub(self) use macros::*;
+use macros::*;
 pub use packet::Packet;
 pub use subscribe::SubscribePacket;
 pub use unsubscribe::UnsubscribePacket;
 
-pub(self) type JsonObject = Map<String, Value>;
+type JsonObject = Map<String, Value>;
@@ -1,8 +1,8 @@
 use std::{collections::HashMap, fmt};
 
 use super::{
-    CommandRequestPacket, CommandResponsePacket, ErrorPacket, EventPacket, SubscribePacket,
-    UnsubscribePacket,
+    CommandRequestPacket, CommandResponsePacket, DataRequestPacket, DataResponsePacket,
+    ErrorPacket, EventPacket, SubscribePacket, UnsubscribePacket,
 };
 use crate::packet::{get_unexpected, DeserializablePacket};
 use serde::{
@@ -11,7 +11,9 @@ use serde::{
 };
 use serde_json::Value;
 
-#[derive(Debug, Clone)]
+const ENVELOPE_HEADERS: [&str; 2] = ["requestId", "messageType"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Packet {
     Error(ErrorPacket),
     Subscribe(SubscribePacket),
@@ -19,6 +21,74 @@ pub enum Packet {
     Event(EventPacket),
     CommandRequest(CommandRequestPacket),
     CommandResponse(CommandResponsePacket),
+    DataRequest(DataRequestPacket),
+    DataResponse(DataResponsePacket),
+}
+
+// Decodes either direction, so captured traffic from both ends of a connection can be
+// read back.
+impl Packet {
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    // Header entries that none of this packet's fields account for, leaving out the
+    // request id and message type since this crate writes those on packets of any kind.
+    pub fn unknown_headers(&self) -> Vec<&str> {
+        let extra_header = match self {
+            Packet::Error(value) => &value.extra_header,
+            Packet::Event(value) => &value.extra_header,
+            Packet::CommandResponse(value) => &value.extra_header,
+            Packet::DataResponse(value) => &value.extra_header,
+            _ => return vec![],
+        };
+
+        extra_header
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !ENVELOPE_HEADERS.contains(key))
+            .collect()
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Packet::Error(value) => match value.request_id {
+                Some(request_id) => write!(
+                    f,
+                    "Error(requestId={}, {}, {:?})",
+                    request_id, value.status_code, value.status_message
+                ),
+                None => write!(f, "Error({}, {:?})", value.status_code, value.status_message),
+            },
+            Packet::Subscribe(value) => write!(f, "Subscribe({:?})", value.event_name),
+            Packet::Unsubscribe(value) => write!(f, "Unsubscribe({:?})", value.event_name),
+            Packet::Event(value) => write!(f, "Event({:?})", value.event_name),
+            Packet::CommandRequest(value) => write!(
+                f,
+                "CommandRequest(requestId={}, {:?})",
+                value.request_id, value.command_line
+            ),
+            Packet::CommandResponse(value) => write!(
+                f,
+                "CommandResponse(requestId={}, {})",
+                value.request_id, value.status_code
+            ),
+            Packet::DataRequest(value) => write!(
+                f,
+                "DataRequest(requestId={}, {:?})",
+                value.request_id, value.data_type
+            ),
+            Packet::DataResponse(value) => {
+                write!(f, "DataResponse(requestId={})", value.request_id)
+            }
+        }
+    }
 }
 
 impl Serialize for Packet {
@@ -26,12 +96,15 @@ impl Serialize for Packet {
     where
         S: Serializer,
     {
-        use serde::ser::Error;
         match self {
+            Packet::Error(value) => value.serialize(serializer),
             Packet::Subscribe(value) => value.serialize(serializer),
             Packet::Unsubscribe(value) => value.serialize(serializer),
+            Packet::Event(value) => value.serialize(serializer),
             Packet::CommandRequest(value) => value.serialize(serializer),
-            _ => Err(Error::custom("unserializable packet")),
+            Packet::CommandResponse(value) => value.serialize(serializer),
+            Packet::DataRequest(value) => value.serialize(serializer),
+            Packet::DataResponse(value) => value.serialize(serializer),
         }
     }
 }
@@ -87,6 +160,21 @@ impl<'de> Visitor<'de> for PacketVisitor {
             "event" => Packet::Event(<EventPacket as DeserializablePacket<
                 A,
             >>::deserialize_map(packet)?),
+            "subscribe" => Packet::Subscribe(
+                <SubscribePacket as DeserializablePacket<A>>::deserialize_map(packet)?,
+            ),
+            "unsubscribe" => Packet::Unsubscribe(
+                <UnsubscribePacket as DeserializablePacket<A>>::deserialize_map(packet)?,
+            ),
+            "commandRequest" => Packet::CommandRequest(
+                <CommandRequestPacket as DeserializablePacket<A>>::deserialize_map(packet)?,
+            ),
+            "dataRequest" => Packet::DataRequest(
+                <DataRequestPacket as DeserializablePacket<A>>::deserialize_map(packet)?,
+            ),
+            "data" => Packet::DataResponse(
+                <DataResponsePacket as DeserializablePacket<A>>::deserialize_map(packet)?,
+            ),
             other => {
                 return Err(Error::invalid_value(
                     Unexpected::Str(other),
@@ -105,3 +193,219 @@ impl<'de> Deserialize<'de> for Packet {
         deserializer.deserialize_map(PacketVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventType;
+
+    fn round_trip(packet: Packet) -> Packet {
+        Packet::from_json(&packet.to_json().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn packets_survive_a_round_trip() {
+        let mut properties = crate::packet::JsonObject::new();
+        properties.insert("message".to_string(), "hi".into());
+        let packets = [
+            Packet::CommandRequest(CommandRequestPacket::new("say hi")),
+            Packet::CommandResponse(CommandResponsePacket {
+                status_code: -1,
+                status_message: Some("nope".to_string()),
+                ..Default::default()
+            }),
+            Packet::Event(EventPacket {
+                event_name: EventType::PlayerMessage,
+                properties,
+                ..Default::default()
+            }),
+            Packet::Error(ErrorPacket {
+                status_message: "broken".to_string(),
+                status_code: -2,
+                request_id: Some(uuid::Uuid::new_v4()),
+                ..Default::default()
+            }),
+            Packet::Subscribe(SubscribePacket {
+                event_name: EventType::PlayerMessage,
+            }),
+            Packet::Unsubscribe(UnsubscribePacket {
+                event_name: EventType::PlayerMessage,
+            }),
+        ];
+
+        for packet in packets {
+            assert_eq!(round_trip(packet.clone()), packet);
+        }
+    }
+
+    #[test]
+    fn display_is_a_one_line_summary() {
+        let request_id = uuid::Uuid::nil();
+        let request = Packet::CommandRequest(CommandRequestPacket {
+            request_id,
+            ..CommandRequestPacket::new("say hi")
+        });
+        assert_eq!(
+            request.to_string(),
+            format!("CommandRequest(requestId={request_id}, \"say hi\")")
+        );
+
+        let event = Packet::Event(EventPacket {
+            event_name: EventType::PlayerMessage,
+            ..Default::default()
+        });
+        assert_eq!(event.to_string(), "Event(PlayerMessage)");
+
+        let error = Packet::Error(ErrorPacket {
+            status_message: "broken".to_string(),
+            status_code: -2,
+            ..Default::default()
+        });
+        assert_eq!(error.to_string(), "Error(-2, \"broken\")");
+    }
+
+    #[test]
+    fn responses_keep_their_version() {
+        let packet = Packet::CommandResponse(CommandResponsePacket {
+            status_message: Some("done".to_string()),
+            protocol_version: 26,
+            ..Default::default()
+        });
+        assert_eq!(round_trip(packet.clone()), packet);
+    }
+
+    #[test]
+    fn events_keep_their_version() {
+        let packet = Packet::Event(EventPacket {
+            event_name: EventType::PlayerMessage,
+            protocol_version: 26,
+            ..Default::default()
+        });
+        let Packet::Event(event) = round_trip(packet) else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.protocol_version, 26);
+    }
+
+    #[test]
+    fn local_packets_claim_version_one() {
+        let packet = Packet::CommandResponse(CommandResponsePacket::default());
+        let json: Value = serde_json::from_str(&packet.to_json().unwrap()).unwrap();
+        assert_eq!(json["header"]["version"], 1);
+    }
+
+    #[test]
+    fn unknown_headers_leave_out_the_envelope() {
+        let packet = Packet::from_json(
+            r#"{
+                "header": {
+                    "version": 1,
+                    "messagePurpose": "event",
+                    "eventName": "PlayerMessage",
+                    "requestId": "00000000-0000-0000-0000-000000000000",
+                    "newHeader": true
+                },
+                "body": {}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(packet.unknown_headers(), ["newHeader"]);
+    }
+
+    #[test]
+    fn data_requests_round_trip() {
+        let packet = Packet::DataRequest(DataRequestPacket::new("block"));
+        let json: Value = serde_json::from_str(&packet.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["header"]["messagePurpose"], "dataRequest");
+        assert_eq!(json["body"]["dataType"], "block");
+        assert_eq!(round_trip(packet.clone()), packet);
+    }
+
+    #[test]
+    fn data_responses_keep_their_body() {
+        let packet = Packet::from_json(
+            r#"{
+                "header": {
+                    "version": 1,
+                    "messagePurpose": "data",
+                    "requestId": "00000000-0000-0000-0000-000000000001"
+                },
+                "body": { "blocks": [{ "id": "stone", "aux": 0 }] }
+            }"#,
+        )
+        .unwrap();
+        let Packet::DataResponse(response) = &packet else {
+            panic!("expected a data response, got {packet}");
+        };
+
+        assert_eq!(response.request_id, uuid::Uuid::from_u128(1));
+        assert_eq!(response.data["blocks"][0]["id"], "stone");
+        assert_eq!(round_trip(packet.clone()), packet);
+    }
+
+    #[test]
+    fn captured_packets_decode_by_purpose() {
+        let id = r#""requestId":"0e2b2e86-6b49-4e0b-9d6f-4a4dbd6b7b9a""#;
+        let samples = [
+            (
+                format!(r#"{{"header":{{"version":1,"messagePurpose":"commandRequest","messageType":"commandRequest",{id}}},"body":{{"origin":{{"origin":"player"}},"commandLine":"say hi","version":1}}}}"#),
+                "commandRequest",
+            ),
+            (
+                format!(r#"{{"header":{{"version":1,"messagePurpose":"commandResponse",{id}}},"body":{{"statusCode":0,"statusMessage":"hi"}}}}"#),
+                "commandResponse",
+            ),
+            (
+                format!(r#"{{"header":{{"version":1,"messagePurpose":"error",{id}}},"body":{{"statusCode":-2147483648,"statusMessage":"Syntax error"}}}}"#),
+                "error",
+            ),
+            (
+                r#"{"header":{"version":1,"messagePurpose":"event","eventName":"PlayerMessage"},"body":{"message":"hi","type":"chat","sender":"Steve","receiver":""}}"#.to_string(),
+                "event",
+            ),
+            (
+                format!(r#"{{"header":{{"version":1,"messagePurpose":"subscribe","messageType":"commandRequest",{id}}},"body":{{"eventName":"PlayerMessage"}}}}"#),
+                "subscribe",
+            ),
+            (
+                format!(r#"{{"header":{{"version":1,"messagePurpose":"unsubscribe","messageType":"commandRequest",{id}}},"body":{{"eventName":"PlayerMessage"}}}}"#),
+                "unsubscribe",
+            ),
+            (
+                format!(r#"{{"header":{{"version":1,"messagePurpose":"dataRequest",{id}}},"body":{{"dataType":"block"}}}}"#),
+                "dataRequest",
+            ),
+            (
+                format!(r#"{{"header":{{"version":1,"messagePurpose":"data",{id}}},"body":{{"blocks":[]}}}}"#),
+                "data",
+            ),
+        ];
+
+        for (json, purpose) in samples {
+            let packet = Packet::from_json(&json).unwrap();
+            let decoded = match packet {
+                Packet::CommandRequest(_) => "commandRequest",
+                Packet::CommandResponse(_) => "commandResponse",
+                Packet::Error(_) => "error",
+                Packet::Event(_) => "event",
+                Packet::Subscribe(_) => "subscribe",
+                Packet::Unsubscribe(_) => "unsubscribe",
+                Packet::DataRequest(_) => "dataRequest",
+                Packet::DataResponse(_) => "data",
+            };
+            assert_eq!(decoded, purpose);
+
+            let encoded: Value = serde_json::from_str(&packet.to_json().unwrap()).unwrap();
+            assert_eq!(encoded["header"]["messagePurpose"], purpose);
+        }
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        assert!(matches!(
+            Packet::from_json("{\"header\":"),
+            Err(crate::Error::JsonParseError(_))
+        ));
+    }
+}
@@ -1,13 +1,48 @@
-use super::serialize_packet;
+use super::{deserialize_packet, serialize_packet};
 use crate::event::EventType;
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct UnsubscribePacket {
     pub event_name: EventType,
 }
 
+deserialize_packet!(
+    UnsubscribePacket; "unsubscribe",
+    body "eventName" => EventType: event_name,
+);
+
+// Mirrors the subscribe header, `messageType` included.
 serialize_packet!(
     UnsubscribePacket; "unsubscribe",
     body "eventName" => EventType: event_name,
     header "messageType" => String: "commandRequest",
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    #[test]
+    fn header_matches_the_game() {
+        let packet = UnsubscribePacket {
+            event_name: EventType::PlayerMessage,
+        };
+        let mut json = serde_json::to_value(packet).unwrap();
+        let request_id = json["header"].as_object_mut().unwrap().remove("requestId");
+
+        assert!(matches!(request_id, Some(Value::String(id)) if Uuid::parse_str(&id).is_ok()));
+        assert_eq!(
+            json,
+            json!({
+                "header": {
+                    "messagePurpose": "unsubscribe",
+                    "messageType": "commandRequest",
+                    "version": 1,
+                },
+                "body": { "eventName": "PlayerMessage" },
+            })
+        );
+    }
+}
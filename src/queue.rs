@@ -0,0 +1,115 @@
+use crate::{
+    packet::{CommandRequestPacket, CommandResponsePacket},
+    Result, Server,
+};
+use std::collections::VecDeque;
+
+// Unlike `Server::send_commands`, each command is only sent once the previous one
+// has been answered, so Minecraft always runs them in the order they were queued.
+#[derive(Debug, Default)]
+pub struct CommandQueue {
+    commands: VecDeque<CommandRequestPacket>,
+}
+
+impl CommandQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enqueue<T: Into<CommandRequestPacket>>(&mut self, command: T) {
+        self.commands.push_back(command.into());
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    // Stops at the first command that fails; everything queued after it is kept.
+    pub async fn run_to_completion(&mut self, server: &Server) -> Result<Vec<CommandResponsePacket>> {
+        let mut responses = Vec::with_capacity(self.commands.len());
+
+        while let Some(command) = self.commands.pop_front() {
+            let response = server.send_raw_command(command).await?;
+            if let Some(error) = response.as_error() {
+                return Err(error);
+            }
+            responses.push(response);
+        }
+
+        Ok(responses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command::SayCommand,
+        test_util::{connect, request_id, response},
+        Error,
+    };
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn commands_run_one_at_a_time_in_order() {
+        let (server, mut client) = connect().await;
+        let mut queue = CommandQueue::new();
+        for message in ["place", "fill", "summon"] {
+            queue.enqueue(SayCommand::new(message));
+        }
+
+        let client_side = async {
+            let mut arrivals = vec![];
+            for _ in 0..3 {
+                let request = client.recv().await;
+                // Nothing else may go out until this one is answered.
+                assert!(client.is_quiet_for(Duration::from_millis(50)).await);
+                arrivals.push(request["body"]["commandLine"].clone());
+                client
+                    .send(response(request_id(&request), 0, json!({})))
+                    .await;
+            }
+            arrivals
+        };
+        let (responses, arrivals) = tokio::join!(queue.run_to_completion(&server), client_side);
+
+        assert_eq!(responses.unwrap().len(), 3);
+        assert_eq!(arrivals, ["say place", "say fill", "say summon"]);
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_failure_keeps_the_rest_queued() {
+        let (server, mut client) = connect().await;
+        let mut queue = CommandQueue::new();
+        for message in ["first", "second", "third"] {
+            queue.enqueue(SayCommand::new(message));
+        }
+
+        let client_side = async {
+            let request = client.recv().await;
+            client
+                .send(response(
+                    request_id(&request),
+                    -1,
+                    json!({ "statusMessage": "no" }),
+                ))
+                .await;
+        };
+        let (result, _) = tokio::join!(queue.run_to_completion(&server), client_side);
+
+        assert!(matches!(
+            result,
+            Err(Error::MinecraftError {
+                status_code: -1,
+                ..
+            })
+        ));
+        assert_eq!(queue.len(), 2);
+    }
+}
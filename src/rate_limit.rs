@@ -0,0 +1,76 @@
+use std::{sync::Mutex, time::Duration};
+use tokio::time::{sleep, Instant};
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+// A token bucket that holds up to one second's worth of commands, so short bursts
+// go through immediately and sustained floods are spread out.
+pub(crate) struct RateLimiter {
+    rate: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(commands_per_second: u32) -> Self {
+        let rate = f64::from(commands_per_second.max(1));
+        Self {
+            rate,
+            bucket: Mutex::new(Bucket {
+                tokens: rate,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    // Takes a token if one is available, otherwise returns how long until one is.
+    fn take(&self) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.rate);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+
+    pub(crate) fn try_acquire(&self) -> bool {
+        self.take().is_none()
+    }
+
+    pub(crate) async fn acquire(&self) {
+        while let Some(wait) = self.take() {
+            sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bursts_pass_and_floods_are_spread_out() {
+        let limiter = RateLimiter::new(10);
+        let started = Instant::now();
+
+        for _ in 0..10 {
+            assert!(limiter.try_acquire());
+        }
+        assert!(!limiter.try_acquire());
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        // Five more at ten a second take half a second between them.
+        for _ in 0..5 {
+            limiter.acquire().await;
+        }
+        assert!(started.elapsed() >= Duration::from_millis(450));
+    }
+}
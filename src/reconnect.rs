@@ -0,0 +1,221 @@
+use crate::{
+    command::Command,
+    event::{Event, EventListener, EventType},
+    packet::CommandResponsePacket,
+    trace, BackoffPolicy, Error, Result, Server, ServerHandle,
+};
+use std::future::Future;
+use tokio::{sync::watch, task::JoinHandle, time::sleep};
+
+// The live connection, or None while the supervisor is between connections.
+type Connection = watch::Receiver<Option<ServerHandle>>;
+
+// Keeps a connection open by calling `connect` again whenever the current one ends,
+// waiting out `backoff` between attempts. `connect` can dial out with `Server::connect`
+// or wait for the game to `/connect` again with `WsListener::accept`.
+pub struct ReconnectingServer {
+    connection: Connection,
+    supervisor: JoinHandle<()>,
+}
+
+impl ReconnectingServer {
+    pub fn new<F, Fut>(connect: F, backoff: BackoffPolicy) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Server>> + Send + 'static,
+    {
+        let (connection_sender, connection) = watch::channel(None);
+
+        Self {
+            connection,
+            supervisor: tokio::spawn(supervise(connect, backoff, connection_sender)),
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection
+            .borrow()
+            .as_ref()
+            .is_some_and(|handle| handle.is_running())
+    }
+
+    // Waits until a connection is up.
+    pub async fn handle(&self) -> Result<ServerHandle> {
+        next_connection(&mut self.connection.clone()).await
+    }
+
+    // Commands aren't retried; one cut off by a disconnect fails like it would on `Server`.
+    pub async fn send_command<T: Command>(&self, request: T) -> Result<T::Response>
+    where
+        T::Response: TryFrom<CommandResponsePacket, Error = Error>,
+    {
+        self.handle().await?.send_command(request).await
+    }
+
+    pub async fn subscribe<T: Event>(&self) -> Result<DurableEventListener<T>> {
+        let mut connection = self.connection.clone();
+        let listener = next_connection(&mut connection)
+            .await?
+            .subscribe::<T>()
+            .await
+            .ok();
+
+        Ok(DurableEventListener {
+            connection,
+            listener,
+        })
+    }
+}
+
+impl Drop for ReconnectingServer {
+    fn drop(&mut self) {
+        // Dropping the current `Server` along with the task closes its connection.
+        self.supervisor.abort();
+    }
+}
+
+async fn supervise<F, Fut>(
+    mut connect: F,
+    mut backoff: BackoffPolicy,
+    connection_sender: watch::Sender<Option<ServerHandle>>,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Server>>,
+{
+    loop {
+        match connect().await {
+            Ok(mut server) => {
+                backoff.reset();
+                connection_sender.send_replace(Some(server.handle()));
+
+                let _result = server.wait_until_closed().await;
+                trace::debug!(result = ?_result, "connection ended, reconnecting");
+                connection_sender.send_replace(None);
+            }
+            Err(_error) => {
+                trace::warn!(error = %_error, "failed to reconnect");
+            }
+        }
+
+        sleep(backoff.next_delay()).await;
+    }
+}
+
+// A handle whose loop has ended is skipped over, since the supervisor may not have
+// cleared it yet.
+async fn next_connection(connection: &mut Connection) -> Result<ServerHandle> {
+    connection
+        .wait_for(|handle| handle.as_ref().is_some_and(|handle| handle.is_running()))
+        .await
+        .map_err(|_| Error::LoopNotRunning)?
+        .clone()
+        .ok_or(Error::LoopNotRunning)
+}
+
+// Subscribes again on every new connection, so it keeps yielding events across
+// reconnects. Events sent while no connection was up are lost.
+pub struct DurableEventListener<T: Event> {
+    connection: Connection,
+    listener: Option<EventListener<T>>,
+}
+
+impl<T: Event> DurableEventListener<T> {
+    pub fn get_type(&self) -> EventType {
+        T::get_type()
+    }
+
+    pub async fn recv(&mut self) -> Result<T> {
+        loop {
+            if let Some(listener) = &mut self.listener {
+                match listener.recv().await {
+                    // The connection it was bound to has ended.
+                    Err(Error::StreamExhausted(_)) => self.listener = None,
+                    result => return result,
+                }
+            }
+
+            // Subscribing only fails if that connection ended too, so just wait for the next.
+            let handle = next_connection(&mut self.connection).await?;
+            self.listener = handle.subscribe::<T>().await.ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command::SayCommand,
+        event::PlayerMessage,
+        test_util::{connect, event, Client},
+    };
+    use serde_json::json;
+    use std::{sync::Arc, time::Duration};
+    use tokio::sync::{mpsc, Mutex};
+
+    // Each connection attempt takes the next result sent down the channel.
+    fn reconnecting_server() -> (ReconnectingServer, mpsc::UnboundedSender<Result<Server>>) {
+        let (server_sender, server_receiver) = mpsc::unbounded_channel();
+        let server_receiver = Arc::new(Mutex::new(server_receiver));
+        let connect = move || {
+            let server_receiver = server_receiver.clone();
+            async move {
+                let server = server_receiver.lock().await.recv().await;
+                server.unwrap_or(Err(Error::LoopNotRunning))
+            }
+        };
+        let backoff = BackoffPolicy::new(Duration::from_millis(1), Duration::from_millis(10));
+
+        (ReconnectingServer::new(connect, backoff), server_sender)
+    }
+
+    async fn send_message(client: &mut Client, message: &str) {
+        let body = json!({ "message": message, "sender": "Steve", "receiver": "", "type": "chat" });
+        client.send(event("PlayerMessage", body)).await;
+    }
+
+    #[tokio::test]
+    async fn listener_survives_a_reconnect() {
+        let (server, server_sender) = reconnecting_server();
+        let (first_server, mut first_client) = connect().await;
+        server_sender.send(Ok(first_server)).ok();
+
+        let mut listener = server.subscribe::<PlayerMessage>().await.unwrap();
+        assert_eq!(
+            first_client.recv().await["header"]["messagePurpose"],
+            "subscribe"
+        );
+        send_message(&mut first_client, "before").await;
+        assert_eq!(listener.recv().await.unwrap().message, "before");
+
+        drop(first_client);
+        let (second_server, mut second_client) = connect().await;
+        server_sender.send(Ok(second_server)).ok();
+
+        let client_side = async {
+            assert_eq!(
+                second_client.recv().await["header"]["messagePurpose"],
+                "subscribe"
+            );
+            send_message(&mut second_client, "after").await;
+        };
+        let (event, _) = tokio::join!(listener.recv(), client_side);
+        assert_eq!(event.unwrap().message, "after");
+        assert!(server.is_connected());
+    }
+
+    #[tokio::test]
+    async fn failed_attempts_are_retried() {
+        let (server, server_sender) = reconnecting_server();
+        server_sender.send(Err(Error::LoopNotRunning)).ok();
+        server_sender.send(Err(Error::IdleTimeout)).ok();
+        let (connected_server, mut client) = connect().await;
+        server_sender.send(Ok(connected_server)).ok();
+
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+        assert_eq!(response.unwrap().message.as_deref(), Some("hello"));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{event::EventType, packet::Packet};
+use futures::{SinkExt, StreamExt};
+use std::collections::{HashSet, VecDeque};
+use tokio::io::DuplexStream;
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+use uuid::Uuid;
+
+// Plays the part of Minecraft for `Server::from_recording`. Events wait until something
+// subscribes to them, as the game wouldn't send them otherwise, and each scripted
+// response or error answers the oldest command still unanswered, taking its request id.
+pub(crate) struct Replayer {
+    client: WebSocketStream<DuplexStream>,
+    subscribed: HashSet<EventType>,
+    pending_requests: VecDeque<Uuid>,
+}
+
+impl Replayer {
+    pub(crate) fn new(client: WebSocketStream<DuplexStream>) -> Self {
+        Self {
+            client,
+            subscribed: HashSet::new(),
+            pending_requests: VecDeque::new(),
+        }
+    }
+
+    // Returns once the server hangs up. After the script runs out the connection stays
+    // open but nothing else is sent.
+    pub(crate) async fn replay(mut self, script: Vec<Packet>) {
+        for packet in script {
+            if self.deliver(packet).await.is_none() {
+                return;
+            }
+        }
+
+        while self.recv().await.is_some() {}
+    }
+
+    async fn deliver(&mut self, mut packet: Packet) -> Option<()> {
+        match &mut packet {
+            Packet::Event(event) => {
+                while !self.subscribed.contains(&event.event_name) {
+                    self.recv().await?;
+                }
+            }
+            Packet::CommandResponse(response) => response.request_id = self.next_request().await?,
+            Packet::Error(error) => error.request_id = Some(self.next_request().await?),
+            _ => {}
+        }
+
+        let text = packet.to_json().ok()?;
+        self.client.send(Message::Text(text)).await.ok()
+    }
+
+    async fn next_request(&mut self) -> Option<Uuid> {
+        while self.pending_requests.is_empty() {
+            self.recv().await?;
+        }
+        self.pending_requests.pop_front()
+    }
+
+    async fn recv(&mut self) -> Option<()> {
+        let text = match self.client.next().await?.ok()? {
+            Message::Text(text) => text,
+            _ => return Some(()),
+        };
+
+        match Packet::from_json(&text).ok()? {
+            Packet::Subscribe(subscribe) => {
+                self.subscribed.insert(subscribe.event_name);
+            }
+            Packet::Unsubscribe(unsubscribe) => {
+                self.subscribed.remove(&unsubscribe.event_name);
+            }
+            Packet::CommandRequest(request) => self.pending_requests.push_back(request.request_id),
+            _ => {}
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::SayCommand,
+        event::{EventType, PlayerMessage},
+        packet::{CommandResponsePacket, ErrorPacket, EventPacket, Packet},
+        Error, Result, Server,
+    };
+    use serde_json::{json, Value};
+
+    fn chat(message: &str) -> Packet {
+        let Value::Object(properties) =
+            json!({ "message": message, "type": "chat", "sender": "Steve", "receiver": "" })
+        else {
+            unreachable!()
+        };
+        Packet::Event(EventPacket {
+            event_name: EventType::PlayerMessage,
+            properties,
+            ..Default::default()
+        })
+    }
+
+    // Echoes two chat messages back with `say`, as a bot under test might.
+    async fn echo_twice(server: &Server) -> Result<Vec<String>> {
+        let mut listener = server.subscribe::<PlayerMessage>().await?;
+        let mut replies = vec![];
+        for _ in 0..2 {
+            let message = listener.recv().await?;
+            let response = server
+                .send_command(SayCommand::new(&message.message))
+                .await?;
+            replies.push(response.message.unwrap_or_default());
+        }
+        Ok(replies)
+    }
+
+    #[tokio::test]
+    async fn handlers_run_against_a_scripted_session() {
+        let reply = |message: &str| {
+            let mut response = CommandResponsePacket::default();
+            response
+                .extra_data
+                .insert("message".to_string(), message.into());
+            Packet::CommandResponse(response)
+        };
+        let script = vec![chat("hi"), reply("hi"), chat("there"), reply("there")];
+        let server = Server::from_recording(script).await;
+
+        let replies = tokio::time::timeout(std::time::Duration::from_secs(5), echo_twice(&server))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(replies, ["hi", "there"]);
+    }
+
+    #[tokio::test]
+    async fn scripted_errors_fail_the_oldest_command() {
+        let error = Packet::Error(ErrorPacket {
+            status_message: "Unknown command".to_string(),
+            status_code: -2147483648,
+            ..Default::default()
+        });
+        let server = Server::from_recording(vec![error]).await;
+
+        let result = server.send_command(SayCommand::new("hi")).await;
+        assert!(matches!(
+            result,
+            Err(Error::MinecraftError {
+                status_code: -2147483648,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    command::SayCommand,
+    event::PlayerMessage,
+    Error, Result, ServerHandle,
+};
+use futures::future::BoxFuture;
+use std::{collections::HashMap, fmt, future::Future, sync::Arc};
+
+// Handlers return the reply to `say` back to the chat, if any.
+pub type ChatHandler =
+    Arc<dyn Fn(ServerHandle, ChatCommand) -> BoxFuture<'static, Result<Option<String>>> + Send + Sync>;
+
+#[derive(Debug)]
+pub struct ChatCommand {
+    pub name: String,
+    pub args: Vec<String>,
+    pub message: PlayerMessage,
+}
+
+#[derive(Clone)]
+pub struct ChatRouter {
+    prefix: String,
+    handlers: HashMap<String, ChatHandler>,
+}
+
+impl ChatRouter {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            handlers: HashMap::new(),
+        }
+    }
+
+    pub fn with_command<F, Fut>(mut self, name: &str, handler: F) -> Self
+    where
+        F: Fn(ServerHandle, ChatCommand) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Option<String>>> + Send + 'static,
+    {
+        self.handlers.insert(
+            name.to_string(),
+            Arc::new(move |server, command| Box::pin(handler(server, command))),
+        );
+        self
+    }
+
+    pub fn parse(&self, message: PlayerMessage) -> Option<ChatCommand> {
+        let mut words = message.message.strip_prefix(&self.prefix)?.split_whitespace();
+        let name = words.next()?.to_string();
+        let args = words.map(str::to_string).collect();
+
+        Some(ChatCommand {
+            name,
+            args,
+            message,
+        })
+    }
+
+    // Runs until the listener fails or a handler returns an error. Messages without the
+    // prefix, or naming a command with no handler, are ignored.
+    pub async fn run(&self, server: &ServerHandle) -> Result<()> {
+        let mut listener = server.subscribe::<PlayerMessage>().await?;
+
+        loop {
+            let message = match listener.recv().await {
+                Ok(message) => message,
+                // Missed messages can't be answered anyway.
+                Err(Error::Lagged(_)) => continue,
+                Err(err) => return Err(err),
+            };
+            let Some(command) = self.parse(message) else {
+                continue;
+            };
+            let Some(handler) = self.handlers.get(&command.name) else {
+                continue;
+            };
+
+            if let Some(reply) = handler(server.clone(), command).await? {
+                server.send_command(SayCommand::new(&reply)).await?;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ChatRouter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChatRouter")
+            .field("prefix", &self.prefix)
+            .field("commands", &self.handlers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{connect, event};
+    use serde_json::json;
+
+    fn chat(message: &str) -> serde_json::Value {
+        event(
+            "PlayerMessage",
+            json!({ "message": message, "type": "chat", "sender": "Steve", "receiver": "" }),
+        )
+    }
+
+    #[test]
+    fn parse_splits_the_name_and_args() {
+        let router = ChatRouter::new("!");
+        let message: PlayerMessage =
+            serde_json::from_value(chat("!tp  Steve Alex")["body"].clone()).unwrap();
+
+        let command = router.parse(message).unwrap();
+        assert_eq!(command.name, "tp");
+        assert_eq!(command.args, ["Steve", "Alex"]);
+    }
+
+    #[tokio::test]
+    async fn ping_is_answered() {
+        let (server, mut client) = connect().await;
+        let router = ChatRouter::new("!").with_command("ping", |_, command| async move {
+            Ok(Some(format!("pong {}", command.message.sender)))
+        });
+        let handle = server.handle();
+        tokio::spawn(async move { router.run(&handle).await });
+
+        assert_eq!(client.recv().await["header"]["messagePurpose"], "subscribe");
+        client.send(chat("ping")).await;
+        client.send(chat("!unknown")).await;
+        client.send(chat("!ping")).await;
+
+        let request = client.answer_command(json!({})).await;
+        assert_eq!(request["body"]["commandLine"], "say pong Steve");
+    }
+}
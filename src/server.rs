@@ -1,60 +1,230 @@
-use self::event_loop::{EventLoop, EventLoopChannels};
+pub(crate) use self::event_loop::Control;
+use self::event_loop::{CloseReply, EventLoop, EventLoopChannels};
 use crate::{
-    command::Command,
+    command::{
+        help::BedrockCommandSchema, Command, HelpCommand, LocalPlayerNameCommand, SayCommand,
+        TellCommand,
+    },
     event::{Event, EventListener, EventType},
-    packet::{CommandRequestPacket, CommandResponsePacket, EventPacket, Packet, SubscribePacket},
-    Error, MultiError, MultiResult, Result,
+    packet::{CommandRequestPacket, CommandResponsePacket, EventPacket, Packet},
+    recording::Replayer,
+    trace, Error, Hooks, MultiError, MultiResult, Result, ServerConfig, ServerHandle,
+};
+use futures::{
+    future::{join_all, BoxFuture},
+    task::noop_waker_ref,
+    FutureExt, Stream,
 };
-use futures::{future::BoxFuture, task::noop_waker_ref, FutureExt};
 use std::{
-    collections::BTreeMap,
+    collections::HashSet,
+    future::Future,
+    net::SocketAddr,
     sync::{
-        atomic::{AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicI64, Ordering},
         Arc,
     },
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    sync::{mpsc, oneshot, watch, Semaphore},
+    sync::{broadcast, mpsc, oneshot},
     task::JoinHandle,
 };
-use tokio_stream::wrappers::WatchStream;
-use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, protocol::Role},
+    MaybeTlsStream, WebSocketStream,
+};
 use uuid::Uuid;
 
-type SentCommand = (
-    Uuid,
-    oneshot::Sender<CommandResponsePacket>,
-);
+pub(crate) type CommandSender = oneshot::Sender<Result<CommandResponsePacket>>;
+
+pub(crate) type SentCommand = (Uuid, CommandSender);
+
+// The packet header version this crate writes, and the one reported for a peer that
+// hasn't sent anything yet.
+const PROTOCOL_VERSION: i64 = 1;
+const RECORDING_BUFFER_SIZE: usize = 64 * 1024;
+
+// The close frame the peer answered a disconnect with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseInfo {
+    pub code: Option<u16>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatTarget {
+    Broadcast,
+    Player(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    // Bedrock doesn't negotiate a version, so this is the header version on the last
+    // response or event the peer sent.
+    pub negotiated_version: i64,
+    pub encrypted: bool,
+    // Known when the crate opened the connection itself, or the caller passed it to
+    // `Server::accept`.
+    pub peer_addr: Option<SocketAddr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopState {
+    Running,
+    Closed,
+    ErrorTaken,
+}
+
+impl LoopState {
+    fn finished(errored: bool) -> Self {
+        if errored {
+            Self::ErrorTaken
+        } else {
+            Self::Closed
+        }
+    }
+}
 
 pub struct Server {
     loop_handle: JoinHandle<Result<()>>,
-    event_receiver: watch::Receiver<EventPacket>,
-    command_sender: mpsc::Sender<SentCommand>,
-    packet_sender: mpsc::Sender<Packet>,
-    subscribed_events: BTreeMap<EventType, Arc<AtomicU32>>,
-    command_semaphore: Arc<Semaphore>,
+    // A finished JoinHandle can't be polled again, so remember once its result is taken.
+    loop_state: LoopState,
+    shutdown_sender: Option<oneshot::Sender<Option<CloseReply>>>,
+    control_sender: mpsc::Sender<Control>,
+    handle: ServerHandle,
+    peer_addr: Option<SocketAddr>,
+    peer_version: Arc<AtomicI64>,
+    encrypted: AtomicBool,
+    // Lives as long as the connection, so reconnecting means fetching it again.
+    local_player_name: Option<String>,
 }
 
 impl Server {
     pub fn spawn<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
         websocket: WebSocketStream<S>,
+    ) -> Self {
+        Self::spawn_with_hooks(websocket, Hooks::default())
+    }
+
+    pub fn spawn_with_hooks<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        websocket: WebSocketStream<S>,
+        hooks: Hooks,
+    ) -> Self {
+        Self::spawn_with_config(websocket, ServerConfig::default().with_hooks(hooks))
+    }
+
+    pub fn spawn_with_config<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        websocket: WebSocketStream<S>,
+        config: ServerConfig,
     ) -> Self {
         let EventLoopChannels {
             event_loop,
             event_receiver,
             command_sender,
             packet_sender,
-        } = EventLoop::new(websocket);
+            unexpected_sender,
+            shutdown_sender,
+            control_sender,
+            peer_version,
+        } = EventLoop::new(websocket, &config);
 
         Self {
             loop_handle: event_loop.spawn(),
-            event_receiver,
-            command_sender,
-            packet_sender,
-            subscribed_events: BTreeMap::new(),
-            command_semaphore: Arc::new(Semaphore::new(100)),
+            loop_state: LoopState::Running,
+            shutdown_sender: Some(shutdown_sender),
+            control_sender: control_sender.clone(),
+            handle: ServerHandle::new(
+                event_receiver,
+                command_sender,
+                packet_sender,
+                unexpected_sender,
+                control_sender,
+                &config,
+            ),
+            peer_addr: None,
+            peer_version,
+            encrypted: AtomicBool::new(false),
+            local_player_name: None,
+        }
+    }
+
+    // Runs against a scripted Minecraft instead of a live one, for testing handlers. See
+    // `Replayer` for how the script is delivered.
+    pub async fn from_recording(script: Vec<Packet>) -> Self {
+        let (server, client) = tokio::io::duplex(RECORDING_BUFFER_SIZE);
+        let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        tokio::spawn(Replayer::new(client).replay(script));
+
+        Self::spawn(WebSocketStream::from_raw_socket(server, Role::Server, None).await)
+    }
+
+    pub(crate) fn with_peer_addr(mut self, peer_addr: SocketAddr) -> Self {
+        self.peer_addr = Some(peer_addr);
+        self
+    }
+
+    // `peer_addr` is reported by `connection_info` as-is. Pass the address from
+    // `TcpListener::accept`, or the one of the socket beneath a TLS or other wrapper.
+    pub async fn accept<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        stream: S,
+        peer_addr: Option<SocketAddr>,
+    ) -> Result<Self> {
+        let server = Self::spawn(tokio_tungstenite::accept_async(stream).await?);
+        Ok(match peer_addr {
+            Some(peer_addr) => server.with_peer_addr(peer_addr),
+            None => server,
+        })
+    }
+
+    pub async fn connect<R: IntoClientRequest + Unpin>(request: R) -> Result<Self> {
+        let (websocket, _) = tokio_tungstenite::connect_async(request).await?;
+        let peer_addr = match websocket.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.peer_addr().ok(),
+            _ => None,
+        };
+
+        let server = Self::spawn(websocket);
+        Ok(match peer_addr {
+            Some(peer_addr) => server.with_peer_addr(peer_addr),
+            None => server,
+        })
+    }
+
+    #[cfg(feature = "encryption")]
+    pub async fn connect_encrypted<R: IntoClientRequest + Unpin>(request: R) -> Result<Self> {
+        let server = Self::connect(request).await?;
+        server.enable_encryption().await?;
+        Ok(server)
+    }
+
+    #[cfg(feature = "encryption")]
+    pub async fn enable_encryption(&self) -> Result<()> {
+        use crate::{encryption::KeyExchange, packet::EnableEncryptionRequest};
+
+        self.assert_running()?;
+
+        let key_exchange = KeyExchange::new()?;
+        let request = EnableEncryptionRequest::new(key_exchange.public_key(), &key_exchange.salt());
+
+        // The loop has to switch ciphers the moment it sees the response, before any
+        // encrypted frame arrives, so it is told about the handshake up front.
+        self.control_sender
+            .send(Control::BeginEncryption {
+                request_id: request.request_id,
+                key_exchange,
+            })
+            .await
+            .map_err(|_| Error::LoopNotRunning)?;
+
+        let response = self.send_raw_command(request.into()).await?;
+        match response.as_error() {
+            Some(error) => Err(error),
+            None => {
+                self.encrypted.store(true, Ordering::SeqCst);
+                Ok(())
+            }
         }
     }
 
@@ -70,6 +240,45 @@ impl Server {
         let loop_result = server.get_loop_result();
         drop(server);
 
+        Self::combine_results(handler_result, loop_result)
+    }
+
+    // Handlers run concurrently, each with its own handle. Only the first handler error,
+    // in the order given, is returned. The rest are dropped, with a warning when the
+    // `tracing` feature is on.
+    pub async fn run_all<
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        H: FnOnce(ServerHandle) -> F,
+        F: Future<Output = Result<()>>,
+    >(
+        websocket: WebSocketStream<S>,
+        handlers: Vec<H>,
+    ) -> MultiResult<()> {
+        let mut server = Server::spawn(websocket);
+        let results = join_all(
+            handlers
+                .into_iter()
+                .map(|handler| handler(server.handle())),
+        )
+        .await;
+        let loop_result = server.get_loop_result();
+        drop(server);
+
+        let mut handler_result = Ok(());
+        for result in results {
+            match (&handler_result, result) {
+                (Ok(()), Err(error)) => handler_result = Err(error),
+                (Err(_), Err(_error)) => {
+                    trace::warn!(error = %_error, "handler failed");
+                }
+                (_, Ok(())) => {}
+            }
+        }
+
+        Self::combine_results(handler_result, loop_result)
+    }
+
+    fn combine_results(handler_result: Result<()>, loop_result: Option<Error>) -> MultiResult<()> {
         match (handler_result, loop_result) {
             (Ok(()), None) => Ok(()),
             (Ok(()), Some(loop_error)) => Err(MultiError::LoopErrored(loop_error)),
@@ -83,10 +292,23 @@ impl Server {
         }
     }
 
+    pub fn handle(&self) -> ServerHandle {
+        self.handle.clone()
+    }
+
     pub fn is_running(&self) -> bool {
         !self.loop_handle.is_finished()
     }
 
+    pub fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            negotiated_version: self.peer_version.load(Ordering::SeqCst),
+            encrypted: self.encrypted.load(Ordering::SeqCst),
+            peer_addr: self.peer_addr,
+        }
+    }
+
+    #[cfg_attr(not(feature = "encryption"), allow(dead_code))]
     fn assert_running(&self) -> Result<()> {
         if self.loop_handle.is_finished() {
             Err(Error::LoopNotRunning)
@@ -95,107 +317,239 @@ impl Server {
         }
     }
 
-    pub async fn recv_raw_event(&mut self) -> Result<EventPacket> {
-        self.assert_running()?;
-        self.event_receiver.borrow_and_update();
-        self.event_receiver.changed().await?;
-        Ok(self.event_receiver.borrow_and_update().clone())
+    pub async fn recv_raw_event(&self) -> Result<EventPacket> {
+        self.handle.recv_raw_event().await
+    }
+
+    pub fn drain_raw_events(&mut self) -> Vec<EventPacket> {
+        self.handle.drain_raw_events()
+    }
+
+    pub fn unexpected_packets(&self) -> broadcast::Receiver<Packet> {
+        self.handle.unexpected_packets()
+    }
+
+    pub async fn send_raw_packet(&self, packet: Packet) -> Result<()> {
+        self.handle.send_raw_packet(packet).await
     }
 
     pub async fn send_raw_command(
-        &mut self,
+        &self,
         command: CommandRequestPacket,
     ) -> Result<CommandResponsePacket> {
-        self.assert_running()?;
+        self.handle.send_raw_command(command).await
+    }
+
+    pub async fn send_raw_command_with_timeout(
+        &self,
+        command: CommandRequestPacket,
+        timeout: Option<Duration>,
+    ) -> Result<CommandResponsePacket> {
+        self.handle
+            .send_raw_command_with_timeout(command, timeout)
+            .await
+    }
 
-        let semaphore = self.command_semaphore.clone();
-        let permit = semaphore.acquire().await;
-        let uuid = command.request_id;
+    pub async fn send_commands(
+        &self,
+        commands: Vec<CommandRequestPacket>,
+    ) -> Vec<Result<CommandResponsePacket>> {
+        self.handle.send_commands(commands).await
+    }
 
-        self.packet_sender
-            .send(Packet::CommandRequest(command))
-            .await?;
+    pub async fn send_command_nowait<T: Into<CommandRequestPacket>>(&self, request: T) -> Result<()> {
+        self.handle.send_command_nowait(request).await
+    }
 
-        let (tx, rx) = oneshot::channel();
-        self.command_sender.send((uuid, tx)).await?;
+    pub async fn send_command<T: Command>(&self, request: T) -> Result<T::Response>
+    where
+        T::Response: TryFrom<CommandResponsePacket, Error = Error>,
+    {
+        self.handle.send_command(request).await
+    }
 
-        let result = rx.await;
-        drop(permit);
-        Ok(result?)
+    pub async fn send_command_with_timeout<T: Command>(
+        &self,
+        request: T,
+        timeout: Option<Duration>,
+    ) -> Result<T::Response>
+    where
+        T::Response: TryFrom<CommandResponsePacket, Error = Error>,
+    {
+        self.handle.send_command_with_timeout(request, timeout).await
     }
 
-    pub async fn send_command<T: Command>(&mut self, request: T) -> Result<T::Response>
+    pub async fn try_send_command<T: Command>(&self, request: T) -> Result<T::Response>
     where
         T::Response: TryFrom<CommandResponsePacket, Error = Error>,
     {
-        let response = self.send_raw_command(request.into()).await?;
-        if response.status_code == 0 {
-            response.try_into()
-        } else {
-            Err(Error::MinecraftError {
-                status_message: response.status_message,
-                status_code: response.status_code,
-            })
+        self.handle.try_send_command(request).await
+    }
+
+    pub fn available_command_permits(&self) -> usize {
+        self.handle.available_command_permits()
+    }
+
+    pub fn add_command_permits(&self, permits: usize) {
+        self.handle.add_command_permits(permits)
+    }
+
+    pub fn forget_permits(&self, permits: usize) -> usize {
+        self.handle.forget_permits(permits)
+    }
+
+    // Every command awaiting a response fails with `Error::CommandCancelled`. Responses
+    // that still arrive for them are reported as unexpected.
+    pub async fn cancel_pending_commands(&self) -> Result<()> {
+        self.control_sender
+            .send(Control::CancelPendingCommands)
+            .await
+            .map_err(|_| Error::LoopNotRunning)
+    }
+
+    // Keeps the first schema seen for each command name, so later overloads are dropped.
+    pub async fn fetch_all_commands(&self) -> Result<Vec<BedrockCommandSchema>> {
+        let first_page = self.send_command(HelpCommand { page: 1 }).await?;
+        let other_pages = join_all(
+            (2..=first_page.page_count).map(|page| self.send_command(HelpCommand { page })),
+        )
+        .await;
+
+        let mut names = HashSet::new();
+        let mut commands = vec![];
+        for page in [Ok(first_page)].into_iter().chain(other_pages) {
+            for command in page?.get_commands() {
+                if names.insert(command.name.clone()) {
+                    commands.push(command);
+                }
+            }
         }
+
+        Ok(commands)
     }
 
-    pub async fn subscribe<T: Event>(&mut self) -> Result<EventListener<T>> {
-        self.assert_running()?;
+    // Fetched on the first call and cached for the rest of the connection.
+    pub async fn local_player_name(&mut self) -> Result<&str> {
+        let name = match self.local_player_name.take() {
+            Some(name) => name,
+            None => self.send_command(LocalPlayerNameCommand).await?.name,
+        };
 
-        let event_name = T::get_type();
-        let ref_count = self
-            .subscribed_events
-            .entry(event_name)
-            .or_insert_with(|| Arc::new(AtomicU32::new(0)))
-            .clone();
+        Ok(self.local_player_name.insert(name))
+    }
 
-        if ref_count.fetch_add(1, Ordering::SeqCst) == 0 {
-            self.packet_sender
-                .send(Packet::Subscribe(SubscribePacket {
-                    event_name,
-                }))
-                .await?;
+    // Broadcasts go out with `say`, so they show the sender's name; players get `tell`.
+    pub async fn send_chat(&self, target: ChatTarget, message: &str) -> Result<()> {
+        match target {
+            ChatTarget::Broadcast => self.send_command(SayCommand::new(message)).await.map(drop),
+            ChatTarget::Player(player) => self
+                .send_command(TellCommand::new(player, message))
+                .await
+                .map(drop),
         }
+    }
+
+    pub async fn subscribe<T: Event>(&self) -> Result<EventListener<T>> {
+        self.handle.subscribe().await
+    }
 
-        Ok(EventListener::new_unchecked(
-            ref_count,
-            self.packet_sender.clone(),
-            WatchStream::from_changes(self.event_receiver.clone()),
-        ))
+    pub fn is_subscribed(&self, event: EventType) -> bool {
+        self.handle.is_subscribed(event)
+    }
+
+    pub async fn event_stream<T: Event>(&self) -> Result<impl Stream<Item = Result<T>>> {
+        self.handle.event_stream().await
+    }
+
+    pub async fn recv_event_once<T: Event>(&self) -> Result<T> {
+        self.handle.recv_event_once().await
     }
 
     pub fn get_loop_result(&mut self) -> Option<Error> {
+        if self.loop_state != LoopState::Running {
+            return None;
+        }
+
         if let Poll::Ready(result) = self.loop_handle.poll_unpin(&mut Context::from_waker(
             noop_waker_ref(),
         )) {
-            if let Ok(result) = result {
-                return result.err();
-            }
+            let error = result.ok().and_then(|result| result.err());
+            self.loop_state = LoopState::finished(error.is_some());
+            return error;
         }
 
         None
     }
 
+    // Once the loop's error has been handed out, by this or `get_loop_result`, later calls
+    // return `Error::LoopErrorTaken` rather than reporting a clean close.
+    pub async fn wait_until_closed(&mut self) -> Result<()> {
+        match self.loop_state {
+            LoopState::Running => {}
+            LoopState::Closed => return Ok(()),
+            LoopState::ErrorTaken => return Err(Error::LoopErrorTaken),
+        }
+
+        let result = (&mut self.loop_handle)
+            .await
+            .map_err(|_| Error::LoopNotRunning)
+            .and_then(|result| result);
+        self.loop_state = LoopState::finished(result.is_err());
+        result
+    }
+
     pub fn close(mut self) {
-        self.loop_handle.abort();
-        self.command_semaphore.close();
-        self.subscribed_events.clear();
+        // Dropping the sender would also stop the loop; sending just makes the intent explicit.
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            shutdown_sender.send(None).ok();
+        }
+        self.handle.close();
+    }
+
+    // Unlike `close`, waits for the peer to acknowledge the close handshake and for the
+    // loop to finish before returning.
+    pub async fn disconnect(mut self) -> Result<CloseInfo> {
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        self.shutdown_sender
+            .take()
+            .ok_or(Error::LoopNotRunning)?
+            .send(Some(reply_sender))
+            .map_err(|_| Error::LoopNotRunning)?;
+        self.handle.close();
+
+        let close_info = reply_receiver.await.map_err(|_| Error::LoopNotRunning)?;
+        self.wait_until_closed().await.ok();
+        close_info
     }
 }
 
 mod event_loop {
-    use super::SentCommand;
+    use super::{CloseInfo, CommandSender, SentCommand, PROTOCOL_VERSION};
+    use crate::packet::CommandResponsePacket;
+    #[cfg(feature = "encryption")]
     use crate::{
-        packet::{EventPacket, Packet},
-        Error, Result,
+        encryption::{Cipher, KeyExchange},
+        packet::EnableEncryptionResponse,
+    };
+    use crate::{
+        packet::{EventPacket, OriginType, Packet},
+        trace, Error, Hooks, Result, ServerConfig,
+    };
+    use futures::SinkExt;
+    use std::{
+        borrow::Cow,
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicI64, Ordering},
+            Arc,
+        },
+        time::Duration,
     };
-    use futures::{executor::block_on, SinkExt};
-    use std::{borrow::Cow, time::Duration};
     use tokio::{
         io::{AsyncRead, AsyncWrite},
-        sync::{mpsc, watch},
+        sync::{broadcast, mpsc, oneshot},
         task::JoinHandle,
-        time::timeout,
+        time::{sleep, timeout, Instant},
     };
     use tokio_stream::{wrappers::ReceiverStream, StreamExt};
     use tokio_tungstenite::{
@@ -205,118 +559,442 @@ mod event_loop {
         },
         WebSocketStream,
     };
+    use uuid::Uuid;
 
-    const CHANNEL_SIZE: usize = u16::MAX as usize;
+    const UNEXPECTED_CHANNEL_SIZE: usize = 64;
+    const CONTROL_CHANNEL_SIZE: usize = 16;
+    const MIN_PRUNE_THRESHOLD: usize = 64;
+    const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
 
     pub struct EventLoopChannels<S: AsyncRead + AsyncWrite + Unpin> {
         pub event_loop: EventLoop<S>,
-        pub event_receiver: watch::Receiver<EventPacket>,
+        pub event_receiver: broadcast::Receiver<EventPacket>,
         pub packet_sender: mpsc::Sender<Packet>,
         pub command_sender: mpsc::Sender<SentCommand>,
+        pub unexpected_sender: broadcast::Sender<Packet>,
+        pub shutdown_sender: oneshot::Sender<Option<CloseReply>>,
+        pub control_sender: mpsc::Sender<Control>,
+        pub peer_version: Arc<AtomicI64>,
+    }
+
+    pub type CloseReply = oneshot::Sender<Result<CloseInfo>>;
+
+    pub enum Control {
+        CancelPendingCommands,
+        // Sent when a caller stops waiting, so its waiter goes before the next prune.
+        ForgetCommand(Uuid),
+        #[cfg(feature = "encryption")]
+        BeginEncryption {
+            request_id: Uuid,
+            key_exchange: KeyExchange,
+        },
     }
 
     pub struct EventLoop<S: AsyncRead + AsyncWrite + Unpin> {
-        sent_commands: Vec<SentCommand>,
+        sent_commands: HashMap<Uuid, CommandSender>,
+        prune_threshold: usize,
         stream: WebSocketStream<S>,
-        event_sender: watch::Sender<EventPacket>,
+        event_sender: broadcast::Sender<EventPacket>,
         packet_receiver: ReceiverStream<Packet>,
         command_receiver: ReceiverStream<SentCommand>,
+        unexpected_sender: broadcast::Sender<Packet>,
+        shutdown_receiver: oneshot::Receiver<Option<CloseReply>>,
+        close_reply: Option<CloseReply>,
+        control_receiver: mpsc::Receiver<Control>,
+        hooks: Hooks,
+        idle_timeout: Option<Duration>,
+        max_packet_size: Option<usize>,
+        strict_headers: bool,
+        default_origin: Option<OriginType>,
+        peer_version: Arc<AtomicI64>,
+        closed: bool,
+        #[cfg(feature = "encryption")]
+        pending_encryption: Option<(Uuid, KeyExchange)>,
+        #[cfg(feature = "encryption")]
+        cipher: Option<Cipher>,
     }
 
     impl<S: AsyncRead + AsyncWrite + Unpin + Send + 'static> EventLoop<S> {
-        fn process_message(message: Message) -> Result<Option<Packet>> {
+        // Fragmented messages never reach this point in pieces: tungstenite buffers
+        // continuation frames and only yields the reassembled message. Permessage-deflate
+        // isn't supported by tungstenite 0.19, so frames are always uncompressed here.
+        fn process_message(&mut self, message: Message) -> Result<Option<Packet>> {
             match message {
                 Message::Text(text) => {
-                    let packet = serde_json::from_str::<Packet>(text.as_str())?;
-                    Ok(Some(packet))
+                    if !self.check_packet_size(text.len()) {
+                        return Ok(None);
+                    }
+                    Ok(self.decode_packet(serde_json::from_str(text.as_str())))
+                }
+                #[cfg(feature = "encryption")]
+                Message::Binary(data) if self.cipher.is_some() => {
+                    if !self.check_packet_size(data.len()) {
+                        return Ok(None);
+                    }
+                    let data = self.cipher.as_mut().unwrap().decrypt(&data);
+                    Ok(self.decode_packet(serde_json::from_slice(&data)))
                 }
+                Message::Binary(data) => {
+                    self.report_error(Error::UnexpectedBinaryFrame(data));
+                    Ok(None)
+                }
+                // Pings are answered in the loop; pongs carry nothing the protocol needs.
+                Message::Ping(_) | Message::Pong(_) => Ok(None),
+                Message::Close(frame) => Err(match frame {
+                    Some(frame) => Error::ConnectionClosed {
+                        code: Some(frame.code.into()),
+                        reason: frame.reason.into_owned(),
+                    },
+                    None => Error::ConnectionClosed {
+                        code: None,
+                        reason: String::new(),
+                    },
+                }),
                 _ => Ok(None),
             }
         }
 
+        // A packet that doesn't parse, say a shape from a newer game version, is reported
+        // and skipped; only transport errors end the loop. So is one with unknown headers
+        // when those are being checked.
+        fn decode_packet(&self, result: serde_json::Result<Packet>) -> Option<Packet> {
+            let packet = result
+                .map_err(|error| self.report_error(Error::JsonParseError(error)))
+                .ok()?;
+
+            if self.strict_headers {
+                let unknown_headers = packet.unknown_headers();
+                if !unknown_headers.is_empty() {
+                    let unknown_headers = unknown_headers.into_iter().map(String::from).collect();
+                    self.report_error(Error::UnknownHeaders(unknown_headers));
+                    return None;
+                }
+            }
+
+            Some(packet)
+        }
+
+        // tungstenite has already buffered the frame by now, within its own message size
+        // limit; this only spares building the far larger parsed JSON. Like a packet that
+        // doesn't parse, an oversized one is reported and skipped.
+        fn check_packet_size(&self, size: usize) -> bool {
+            match self.max_packet_size {
+                Some(limit) if size > limit => {
+                    self.report_error(Error::PacketTooLarge { size, limit });
+                    false
+                }
+                _ => true,
+            }
+        }
+
+        // Only responses and events keep the version they arrived with.
+        fn record_peer_version(&self, packet: &Packet) {
+            if let Packet::CommandResponse(CommandResponsePacket { protocol_version, .. })
+            | Packet::Event(EventPacket { protocol_version, .. }) = packet
+            {
+                self.peer_version.store(*protocol_version, Ordering::SeqCst);
+            }
+        }
+
+        fn encode_packet(&mut self, packet: &Packet) -> Result<Message> {
+            let text = serde_json::to_string(packet)?;
+
+            #[cfg(feature = "encryption")]
+            if let Some(cipher) = self.cipher.as_mut() {
+                return Ok(Message::Binary(cipher.encrypt(text.as_bytes())));
+            }
+
+            Ok(Message::Text(text))
+        }
+
+        fn handle_control(&mut self, control: Control) {
+            match control {
+                Control::CancelPendingCommands => {
+                    // Commands registered before the cancel may still be queued behind it.
+                    while let Ok(command) = self.command_receiver.as_mut().try_recv() {
+                        self.register_command(command);
+                    }
+
+                    for (request_id, sender) in self.sent_commands.drain() {
+                        Self::resolve_command(sender, Err(Error::CommandCancelled(request_id)));
+                    }
+                }
+                Control::ForgetCommand(request_id) => {
+                    // The registration can still be queued if the caller gave up right away.
+                    while let Ok(command) = self.command_receiver.as_mut().try_recv() {
+                        self.register_command(command);
+                    }
+
+                    self.sent_commands.remove(&request_id);
+                }
+                #[cfg(feature = "encryption")]
+                Control::BeginEncryption {
+                    request_id,
+                    key_exchange,
+                } => self.pending_encryption = Some((request_id, key_exchange)),
+            }
+        }
+
+        #[cfg(feature = "encryption")]
+        fn complete_encryption(&mut self, response: &CommandResponsePacket) -> Result<()> {
+            let key_exchange = match self.pending_encryption.take() {
+                Some((request_id, key_exchange)) if request_id == response.request_id => {
+                    key_exchange
+                }
+                pending => {
+                    self.pending_encryption = pending;
+                    return Ok(());
+                }
+            };
+
+            if response.is_error() {
+                return Ok(());
+            }
+
+            let response = EnableEncryptionResponse::try_from(response)?;
+            self.cipher = Some(key_exchange.complete(&response.public_key)?);
+            Ok(())
+        }
+
+        // Errors that don't warrant tearing down the connection.
+        fn report_error(&self, error: Error) {
+            trace::warn!(%error, "non-fatal event loop error");
+            self.hooks.error(&error);
+        }
+
+        fn report_unexpected(&self, packet: Packet) {
+            trace::warn!(%packet, "unexpected packet received");
+            // Nobody listening for unexpected packets is fine, they are just dropped.
+            self.unexpected_sender.send(packet).ok();
+        }
+
+        fn resolve_command(sender: CommandSender, result: Result<CommandResponsePacket>) {
+            // A failed send just means the caller stopped waiting, which isn't the loop's problem.
+            sender.send(result).ok();
+        }
+
+        fn register_command(&mut self, (request_id, sender): SentCommand) {
+            self.sent_commands.insert(request_id, sender);
+
+            // Cancelled callers normally have their entry forgotten, but one whose forget
+            // didn't fit in the control channel stays until a response shows up, which may be
+            // never. Sweeping only once the list has doubled keeps this amortised O(1).
+            if self.sent_commands.len() >= self.prune_threshold {
+                self.sent_commands
+                    .retain(|_, sender| !sender.is_closed());
+                self.prune_threshold = (self.sent_commands.len() * 2).max(MIN_PRUNE_THRESHOLD);
+            }
+        }
+
         async fn handle_packet(&mut self, packet: Packet) -> Result<()> {
             match packet.clone() {
-                Packet::Event(event) => self.event_sender.send(event).map_err(|err| err.into()),
+                Packet::Event(event) => self
+                    .event_sender
+                    .send(event)
+                    .map(|_| ())
+                    .map_err(|err| err.into()),
 
-                Packet::Error(error) => Err(Error::MinecraftError {
-                    status_message: Some(error.status_message),
-                    status_code: error.status_code,
-                }),
+                Packet::Error(error) => {
+                    let minecraft_error = Error::MinecraftError {
+                        status_message: Some(error.status_message),
+                        status_code: error.status_code,
+                    };
+
+                    // Errors tied to an in-flight command only fail that command. The rest,
+                    // whether naming a command that was already answered or naming none at
+                    // all, can't be pinned on anyone and are only reported.
+                    match error
+                        .request_id
+                        .and_then(|request_id| self.sent_commands.remove(&request_id))
+                    {
+                        Some(sender) => Self::resolve_command(sender, Err(minecraft_error)),
+                        None => self.report_error(minecraft_error),
+                    }
+                    Ok(())
+                }
 
-                Packet::CommandResponse(response) => self
+                Packet::CommandResponse(response) => match self
                     .sent_commands
-                    .swap_remove(
-                        self.sent_commands
-                            .iter()
-                            .position(|(id, _)| id == &response.request_id)
-                            .ok_or(Error::UnexpectedPacket(packet))?,
-                    )
-                    .1
-                    .send(response)
-                    .ok()
-                    .ok_or(Error::CommandHandlingError),
-
-                _ => Err(Error::UnexpectedPacket(packet)),
+                    .remove(&response.request_id)
+                {
+                    Some(sender) => {
+                        trace::debug!(request_id = %response.request_id, "command response received");
+
+                        #[cfg(feature = "encryption")]
+                        let response = self.complete_encryption(&response).map(|_| response);
+                        #[cfg(not(feature = "encryption"))]
+                        let response = Ok(response);
+
+                        Self::resolve_command(sender, response);
+                        Ok(())
+                    }
+                    // Answers to fire-and-forget commands end up here as a matter of course,
+                    // alongside duplicates and answers to commands that timed out or were
+                    // cancelled, so they're published without a warning.
+                    None => {
+                        trace::debug!(request_id = %response.request_id, "response to an untracked command");
+                        self.unexpected_sender.send(packet).ok();
+                        Ok(())
+                    }
+                },
+
+                // Nothing tracks data requests, so their responses are left for whoever
+                // sent them to pick up from `unexpected_packets`.
+                _ => {
+                    self.report_unexpected(packet);
+                    Ok(())
+                }
+            }
+        }
+
+        async fn close(&mut self) {
+            let result = timeout(
+                CLOSE_TIMEOUT,
+                self.stream.close(Some(CloseFrame {
+                    code: CloseCode::Away,
+                    reason: Cow::Borrowed("Connection closing"),
+                })),
+            )
+            .await;
+            self.closed = true;
+
+            if result.is_err() {
+                trace::warn!("websocket could not gracefully close in time");
             }
         }
 
         async fn event_loop(mut self) -> Result<()> {
+            let result = self.run().await;
+            self.close().await;
+            if let Some(close_reply) = self.close_reply.take() {
+                close_reply.send(self.await_close_ack().await).ok();
+            }
+            result
+        }
+
+        async fn await_close_ack(&mut self) -> Result<CloseInfo> {
+            let ack = async {
+                while let Some(message) = self.stream.next().await {
+                    if let Message::Close(frame) = message? {
+                        return Ok(match frame {
+                            Some(frame) => CloseInfo {
+                                code: Some(frame.code.into()),
+                                reason: frame.reason.into_owned(),
+                            },
+                            None => CloseInfo {
+                                code: None,
+                                reason: String::new(),
+                            },
+                        });
+                    }
+                }
+                Err(Error::StreamExhausted("websocket"))
+            };
+
+            timeout(CLOSE_TIMEOUT, ack)
+                .await
+                .map_err(|_| Error::CloseNotAcknowledged)?
+        }
+
+        async fn run(&mut self) -> Result<()> {
+            let idle = sleep(self.idle_timeout.unwrap_or_default());
+            tokio::pin!(idle);
+
             loop {
                 tokio::select! {
                     biased;
 
-                    command_future = self.command_receiver.next() => self.sent_commands.push(
+                    // Resolves on an explicit shutdown or when the server is dropped.
+                    close_reply = &mut self.shutdown_receiver => {
+                        self.close_reply = close_reply.ok().flatten();
+                        return Ok(());
+                    }
+
+                    Some(control) = self.control_receiver.recv() => self.handle_control(control),
+
+                    command_future = self.command_receiver.next() => self.register_command(
                         command_future.ok_or(Error::StreamExhausted("command"))?
                     ),
 
+                    _ = &mut idle, if self.idle_timeout.is_some() => return Err(Error::IdleTimeout),
+
                     message = self.stream.try_next() => {
-                        if let Some(packet) = Self::process_message(
-                            message?.ok_or(Error::StreamExhausted("websocket"))?
-                        )? {
+                        let message = message?.ok_or(Error::StreamExhausted("websocket"))?;
+                        if let Some(idle_timeout) = self.idle_timeout {
+                            idle.as_mut().reset(Instant::now() + idle_timeout);
+                        }
+
+                        // tungstenite queues the pong reply while reading a ping but only writes
+                        // it alongside the next frame; flush so it goes out right away.
+                        if message.is_ping() {
+                            self.stream.flush().await?;
+                        }
+
+                        if let Some(packet) = self.process_message(message)? {
+                            self.record_peer_version(&packet);
+                            self.hooks.inbound(&packet);
                             self.handle_packet(packet).await?;
                         }
                     },
 
-                    packet = self.packet_receiver.next() =>
-                        self.stream.send(Message::Text(serde_json::to_string(
-                            &packet.ok_or(Error::StreamExhausted("packet"))?
-                        )?)).await?,
-                }
-            }
-        }
+                    packet = self.packet_receiver.next() => {
+                        let mut packet = packet.ok_or(Error::StreamExhausted("packet"))?;
+                        if let (Some(origin_type), Packet::CommandRequest(request)) =
+                            (self.default_origin, &mut packet)
+                        {
+                            request.origin.origin_type = origin_type;
+                        }
 
-        pub fn new_from_raw(
-            stream: WebSocketStream<S>,
-            event_sender: watch::Sender<EventPacket>,
-            packet_receiver: ReceiverStream<Packet>,
-            command_receiver: ReceiverStream<SentCommand>,
-        ) -> Self {
-            Self {
-                sent_commands: vec![],
-                stream,
-                event_sender,
-                packet_receiver,
-                command_receiver,
+                        self.hooks.outbound(&packet);
+                        let message = self.encode_packet(&packet)?;
+                        self.stream.send(message).await?;
+                    },
+                }
             }
         }
 
-        pub fn new(stream: WebSocketStream<S>) -> EventLoopChannels<S> {
-            let (event_tx, mut event_rx) = watch::channel(EventPacket::default());
-            let (command_tx, command_rx) = mpsc::channel(CHANNEL_SIZE);
-            let (packet_tx, packet_rx) = mpsc::channel(CHANNEL_SIZE);
-
-            event_rx.borrow_and_update();
+        #[allow(clippy::new_ret_no_self)]
+        pub fn new(stream: WebSocketStream<S>, config: &ServerConfig) -> EventLoopChannels<S> {
+            // Channels panic when created with no capacity.
+            let (event_tx, event_rx) = broadcast::channel(config.event_channel_size.max(1));
+            let (command_tx, command_rx) = mpsc::channel(config.command_channel_size.max(1));
+            let (packet_tx, packet_rx) = mpsc::channel(config.packet_channel_size.max(1));
+            let (unexpected_tx, _) = broadcast::channel(UNEXPECTED_CHANNEL_SIZE);
+            let (shutdown_tx, shutdown_rx) = oneshot::channel();
+            let (control_tx, control_rx) = mpsc::channel(CONTROL_CHANNEL_SIZE);
+            let peer_version = Arc::new(AtomicI64::new(PROTOCOL_VERSION));
 
             EventLoopChannels {
-                event_loop: Self::new_from_raw(
+                event_loop: Self {
+                    sent_commands: HashMap::new(),
+                    prune_threshold: MIN_PRUNE_THRESHOLD,
                     stream,
-                    event_tx,
-                    packet_rx.into(),
-                    command_rx.into(),
-                ),
-                event_receiver: event_rx.into(),
+                    event_sender: event_tx,
+                    packet_receiver: packet_rx.into(),
+                    command_receiver: command_rx.into(),
+                    unexpected_sender: unexpected_tx.clone(),
+                    shutdown_receiver: shutdown_rx,
+                    close_reply: None,
+                    control_receiver: control_rx,
+                    hooks: config.hooks.clone(),
+                    idle_timeout: config.idle_timeout,
+                    max_packet_size: config.max_packet_size,
+                    strict_headers: config.strict_headers,
+                    default_origin: config.default_origin,
+                    peer_version: peer_version.clone(),
+                    closed: false,
+                    #[cfg(feature = "encryption")]
+                    pending_encryption: None,
+                    #[cfg(feature = "encryption")]
+                    cipher: None,
+                },
+                event_receiver: event_rx,
                 packet_sender: packet_tx,
                 command_sender: command_tx,
+                unexpected_sender: unexpected_tx,
+                shutdown_sender: shutdown_tx,
+                control_sender: control_tx,
+                peer_version,
             }
         }
 
@@ -330,17 +1008,837 @@ mod event_loop {
             self.command_receiver.close();
             self.packet_receiver.close();
 
-            let result = block_on(timeout(
-                Duration::from_millis(10),
-                self.stream.close(Some(CloseFrame {
-                    code: CloseCode::Away,
-                    reason: Cow::Borrowed("Connection closing"),
-                })),
-            ));
+            // The close handshake happens asynchronously at the end of the loop, so getting
+            // here without it means the task was aborted or its runtime shut down.
+            if !self.closed {
+                trace::warn!("event loop dropped before the websocket was closed");
+            }
+        }
+    }
+}
 
-            if let Err(_) = result {
-                eprintln!("websocket could not gracefully close in time.");
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::SayCommand,
+        packet::{OriginType, Packet},
+        test_util::{
+            connect, connect_with_config, error_log, event, request_id, response, websocket_pair,
+        },
+        ChatTarget, Error, Hooks, MultiError, Result, Server, ServerConfig, ServerHandle,
+    };
+    use serde_json::json;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::{
+        protocol::{
+            frame::{
+                coding::{CloseCode, Data, OpCode},
+                Frame,
+            },
+            CloseFrame,
+        },
+        Message,
+    };
+
+    #[tokio::test]
+    async fn pings_are_answered() {
+        let (_server, mut client) = connect().await;
+
+        client
+            .send_frame(Message::Ping(b"keepalive".to_vec()))
+            .await;
+        assert_eq!(
+            client.recv_frame().await,
+            Message::Pong(b"keepalive".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn silent_connections_time_out() {
+        let config = ServerConfig::default().with_idle_timeout(Some(Duration::from_millis(50)));
+        let (mut server, _client) = connect_with_config(config).await;
+
+        let result = tokio::time::timeout(Duration::from_secs(5), server.wait_until_closed()).await;
+        assert!(matches!(result, Ok(Err(Error::IdleTimeout))));
+    }
+
+    // `tokio::test` runs on a current-thread runtime, where blocking in `Drop` would hang.
+    #[tokio::test]
+    async fn dropping_the_server_closes_the_connection() {
+        let (server, mut client) = connect().await;
+        drop(server);
+
+        match client.recv_frame().await {
+            Message::Close(Some(frame)) => assert_eq!(u16::from(frame.code), 1001),
+            other => panic!("expected a close frame, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn peer_close_frames_are_surfaced() {
+        let (mut server, mut client) = connect().await;
+
+        client
+            .send_frame(Message::Close(Some(CloseFrame {
+                code: CloseCode::Library(4000),
+                reason: "world unloaded".into(),
+            })))
+            .await;
+
+        let result = tokio::time::timeout(Duration::from_secs(5), server.wait_until_closed()).await;
+        match result {
+            Ok(Err(Error::ConnectionClosed { code, reason })) => {
+                assert_eq!(code, Some(4000));
+                assert_eq!(reason, "world unloaded");
             }
+            other => panic!("expected the close to be surfaced, got {other:?}"),
         }
     }
+
+    #[tokio::test]
+    async fn closing_interrupts_permit_waits_with_a_shutdown_error() {
+        let config = ServerConfig::default().with_max_in_flight_commands(1);
+        let (server, mut client) = connect_with_config(config).await;
+        let send = |message: &'static str| {
+            let handle = server.handle();
+            tokio::spawn(async move { handle.send_command(SayCommand::new(message)).await })
+        };
+
+        let _first = send("first");
+        client.recv().await;
+        let second = send("second");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        server.close();
+
+        assert!(matches!(
+            second.await.unwrap(),
+            Err(Error::ServerShuttingDown)
+        ));
+    }
+
+    #[tokio::test]
+    async fn disconnect_returns_the_acknowledged_close() {
+        let (server, mut client) = connect().await;
+
+        let client_side = async {
+            assert!(matches!(client.recv_frame().await, Message::Close(Some(_))));
+            // Reading on flushes tungstenite's automatic reply.
+            client.is_quiet_for(Duration::from_millis(100)).await;
+        };
+        let (close_info, _) = tokio::join!(server.disconnect(), client_side);
+
+        let close_info = close_info.unwrap();
+        assert_eq!(close_info.code, Some(1001));
+        assert_eq!(close_info.reason, "Connection closing");
+    }
+
+    #[tokio::test]
+    async fn unacknowledged_closes_time_out() {
+        // The client never reads, so the close frame is never answered.
+        let (server, _client) = connect().await;
+        let handle = server.handle();
+
+        let started = tokio::time::Instant::now();
+        let result = tokio::time::timeout(Duration::from_secs(5), server.disconnect()).await;
+        assert!(matches!(result, Ok(Err(Error::CloseNotAcknowledged))));
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert!(!handle.is_running());
+    }
+
+    #[tokio::test]
+    async fn inbound_messages_reset_the_idle_timeout() {
+        let config = ServerConfig::default().with_idle_timeout(Some(Duration::from_millis(100)));
+        let (server, mut client) = connect_with_config(config).await;
+
+        for _ in 0..5 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            client.send_frame(Message::Ping(vec![])).await;
+            client.recv_frame().await;
+        }
+        assert!(server.is_running());
+    }
+
+    #[tokio::test]
+    async fn undecodable_packets_are_skipped() {
+        let (server, mut client) = connect().await;
+
+        client
+            .send_frame(Message::Text("not json".to_string()))
+            .await;
+        client
+            .send(json!({ "header": { "version": 1, "messagePurpose": "fromTheFuture" }, "body": {} }))
+            .await;
+
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+        assert_eq!(response.unwrap().message.as_deref(), Some("hello"));
+    }
+
+    #[tokio::test]
+    async fn duplicate_responses_are_not_fatal() {
+        let (server, mut client) = connect().await;
+        let mut unexpected = server.unexpected_packets();
+
+        let (first, request) = tokio::join!(
+            server.send_command(SayCommand::new("once")),
+            client.answer_command(json!({ "message": "once" })),
+        );
+        assert!(first.is_ok());
+
+        let request_id = request_id(&request);
+        client
+            .send(response(request_id, 0, json!({ "message": "once" })))
+            .await;
+        client
+            .send(json!({
+                "header": { "version": 1, "messagePurpose": "error", "requestId": request_id },
+                "body": { "statusCode": -1, "statusMessage": "late failure" },
+            }))
+            .await;
+        assert!(matches!(
+            unexpected.recv().await,
+            Ok(Packet::CommandResponse(_))
+        ));
+
+        let (second, _) = tokio::join!(
+            server.send_command(SayCommand::new("twice")),
+            client.answer_command(json!({ "message": "twice" })),
+        );
+        assert_eq!(second.unwrap().message.as_deref(), Some("twice"));
+        assert!(server.is_running());
+    }
+
+    #[tokio::test]
+    async fn unexpected_packets_are_published_and_skipped() {
+        let (server, mut client) = connect().await;
+        let mut unexpected = server.unexpected_packets();
+
+        client
+            .send(json!({
+                "header": { "version": 1, "messagePurpose": "commandRequest", "requestId": uuid::Uuid::new_v4() },
+                "body": { "origin": { "origin": "player" }, "commandLine": "say backwards" },
+            }))
+            .await;
+        match unexpected.recv().await {
+            Ok(Packet::CommandRequest(request)) => {
+                assert_eq!(request.command_line, "say backwards")
+            }
+            other => panic!("expected the stray command request, got {other:?}"),
+        }
+
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("still here")),
+            client.answer_command(json!({ "message": "still here" })),
+        );
+        assert_eq!(response.unwrap().message.as_deref(), Some("still here"));
+        assert!(server.is_running());
+    }
+
+    #[tokio::test]
+    async fn hooks_see_every_packet_crossing_the_loop() {
+        let events = Arc::new(AtomicUsize::new(0));
+        let outbound = Arc::new(Mutex::new(vec![]));
+        let hooks = {
+            let events = events.clone();
+            let outbound = outbound.clone();
+            Hooks::default()
+                .with_inbound(move |packet| {
+                    if matches!(packet, Packet::Event(_)) {
+                        events.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+                .with_outbound(move |packet| {
+                    if let Packet::CommandRequest(request) = packet {
+                        outbound.lock().unwrap().push(request.command_line.clone());
+                    }
+                })
+        };
+        let (websocket, mut client) = websocket_pair().await;
+        let server = Server::spawn_with_hooks(websocket, hooks);
+
+        for _ in 0..3 {
+            client
+                .send(event("PlayerMessage", json!({ "message": "hi" })))
+                .await;
+        }
+        // The loop handles packets in order, so the events are counted by the time the
+        // command comes back.
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("counted")),
+            client.answer_command(json!({ "message": "counted" })),
+        );
+        assert!(response.is_ok());
+
+        assert_eq!(events.load(Ordering::SeqCst), 3);
+        assert_eq!(*outbound.lock().unwrap(), ["say counted"]);
+    }
+
+    #[tokio::test]
+    async fn unencrypted_binary_frames_are_reported() {
+        let (hooks, errors) = error_log();
+        let (server, mut client) =
+            connect_with_config(ServerConfig::default().with_hooks(hooks)).await;
+
+        client.send_frame(Message::Binary(vec![1, 2, 3])).await;
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+
+        assert!(response.is_ok());
+        assert_eq!(
+            *errors.lock().unwrap(),
+            ["unexpected binary frame of 3 bytes"]
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_commands_are_reaped() {
+        let (server, mut client) = connect().await;
+        let mut unexpected = server.unexpected_packets();
+
+        // The command future is dropped as soon as its request reaches the client.
+        let request = tokio::select! {
+            _ = server.send_command(SayCommand::new("cancelled")) => unreachable!(),
+            request = client.recv() => request,
+        };
+        client
+            .send(response(
+                request_id(&request),
+                0,
+                json!({ "message": "cancelled" }),
+            ))
+            .await;
+
+        // A lingering entry would swallow the late response instead of publishing it.
+        match unexpected.recv().await {
+            Ok(Packet::CommandResponse(response)) => {
+                assert_eq!(response.request_id.to_string(), request_id(&request))
+            }
+            other => panic!("expected the late response, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn timed_out_commands_are_forgotten() {
+        let (server, mut client) = connect().await;
+        let mut unexpected = server.unexpected_packets();
+
+        let (result, request) = tokio::join!(
+            server.send_command_with_timeout(
+                SayCommand::new("slow"),
+                Some(Duration::from_millis(10))
+            ),
+            client.recv(),
+        );
+        assert!(matches!(result, Err(Error::CommandTimedOut(_))));
+
+        // Far below the prune threshold, so only the forget can have removed the entry.
+        client
+            .send(response(
+                request_id(&request),
+                0,
+                json!({ "message": "slow" }),
+            ))
+            .await;
+        match unexpected.recv().await {
+            Ok(Packet::CommandResponse(response)) => {
+                assert_eq!(response.request_id.to_string(), request_id(&request))
+            }
+            other => panic!("expected the late response, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn many_in_flight_commands_are_correlated() {
+        const COMMANDS: usize = 2000;
+        let config = ServerConfig::default().with_max_in_flight_commands(COMMANDS);
+        let (server, mut client) = connect_with_config(config).await;
+
+        let commands = (0..COMMANDS).map(|i| SayCommand::new(&i.to_string()));
+        let client_side = async {
+            let mut requests = vec![];
+            for _ in 0..COMMANDS {
+                requests.push(client.recv().await);
+            }
+            for request in requests.iter().rev() {
+                let message = request["body"]["commandLine"].as_str().unwrap();
+                client
+                    .send(response(
+                        request_id(request),
+                        0,
+                        json!({ "message": message }),
+                    ))
+                    .await;
+            }
+        };
+        let started = tokio::time::Instant::now();
+        let (results, _) = tokio::join!(
+            futures::future::join_all(commands.map(|command| server.send_command(command))),
+            client_side,
+        );
+
+        for (i, result) in results.into_iter().enumerate() {
+            assert_eq!(result.unwrap().message, Some(format!("say {i}")));
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn tiny_channels_still_round_trip() {
+        let config = ServerConfig::default()
+            .with_command_channel_size(1)
+            .with_packet_channel_size(1)
+            .with_event_channel_size(1);
+        let (server, mut client) = connect_with_config(config).await;
+
+        let commands =
+            ["one", "two", "three"].map(|message| server.send_command(SayCommand::new(message)));
+        let client_side = async {
+            for _ in 0..3 {
+                let request = client.recv().await;
+                let message = request["body"]["commandLine"].as_str().unwrap();
+                client
+                    .send(response(
+                        request_id(&request),
+                        0,
+                        json!({ "message": message }),
+                    ))
+                    .await;
+            }
+        };
+        let (results, _) = tokio::join!(futures::future::join_all(commands), client_side);
+
+        let messages: Vec<_> = results
+            .into_iter()
+            .map(|result| result.unwrap().message.unwrap())
+            .collect();
+        assert_eq!(messages, ["say one", "say two", "say three"]);
+    }
+
+    #[tokio::test]
+    async fn cancelling_fails_every_pending_command() {
+        let (server, mut client) = connect().await;
+        let mut unexpected = server.unexpected_packets();
+
+        let client_side = async {
+            let first = client.recv().await;
+            let second = client.recv().await;
+            server.cancel_pending_commands().await.unwrap();
+            (first, second)
+        };
+        let (first, second, (first_request, second_request)) = tokio::join!(
+            server.send_command(SayCommand::new("first")),
+            server.send_command(SayCommand::new("second")),
+            client_side,
+        );
+        assert!(matches!(
+            first,
+            Err(Error::CommandCancelled(id)) if id.to_string() == request_id(&first_request)
+        ));
+        assert!(matches!(second, Err(Error::CommandCancelled(_))));
+
+        // A late answer to a cancelled command belongs to nobody.
+        client
+            .send(response(request_id(&second_request), 0, json!({})))
+            .await;
+        assert!(matches!(
+            unexpected.recv().await,
+            Ok(Packet::CommandResponse(_))
+        ));
+        assert!(server.is_running());
+    }
+
+    #[tokio::test]
+    async fn chat_picks_say_or_tell_by_target() {
+        let (server, mut client) = connect().await;
+
+        let (result, request) = tokio::join!(
+            server.send_chat(ChatTarget::Broadcast, "hello all"),
+            client.answer_command(json!({ "message": "hello all" })),
+        );
+        assert!(result.is_ok());
+        assert_eq!(request["body"]["commandLine"], "say hello all");
+
+        let (result, request) = tokio::join!(
+            server.send_chat(ChatTarget::Player("Big Steve".to_string()), "psst"),
+            client.answer_command(json!({ "statusMessage": "You whisper to Big Steve: psst" })),
+        );
+        assert!(result.is_ok());
+        assert_eq!(request["body"]["commandLine"], "tell \"Big Steve\" psst");
+    }
+
+    #[tokio::test]
+    async fn default_origin_replaces_each_command_origin() {
+        let (server, mut client) = connect().await;
+        let (_, request) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+        assert_eq!(request["body"]["origin"], json!({ "origin": "player" }));
+
+        let config =
+            ServerConfig::default().with_default_origin(Some(OriginType::AutomationPlayer));
+        let (server, mut client) = connect_with_config(config).await;
+        let (_, request) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+        assert_eq!(
+            request["body"]["origin"],
+            json!({ "origin": "automationPlayer" })
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_packets_are_skipped() {
+        let (hooks, errors) = error_log();
+        let config = ServerConfig::default()
+            .with_max_packet_size(Some(256))
+            .with_hooks(hooks);
+        let (server, mut client) = connect_with_config(config).await;
+
+        client.send_frame(Message::Text("[".repeat(1024))).await;
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+
+        assert!(response.is_ok());
+        assert_eq!(
+            *errors.lock().unwrap(),
+            ["packet of 1024 bytes exceeds the 256 byte limit"]
+        );
+    }
+
+    #[tokio::test]
+    async fn accept_records_the_peer_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let client = async {
+            let stream = TcpStream::connect(listener.local_addr().unwrap())
+                .await
+                .unwrap();
+            let client_addr = stream.local_addr().unwrap();
+            let (websocket, _) = tokio_tungstenite::client_async(url, stream).await.unwrap();
+            (websocket, client_addr)
+        };
+        let server = async {
+            let (stream, peer_addr) = listener.accept().await.unwrap();
+            Server::accept(stream, Some(peer_addr)).await.unwrap()
+        };
+
+        let (server, (_websocket, client_addr)) = tokio::join!(server, client);
+        assert_eq!(server.connection_info().peer_addr, Some(client_addr));
+    }
+
+    #[tokio::test]
+    async fn accept_works_over_any_stream() {
+        let (server_stream, client_stream) = tokio::io::duplex(1024);
+        let client = tokio_tungstenite::client_async("ws://localhost", client_stream);
+
+        let (server, client) = tokio::join!(Server::accept(server_stream, None), client);
+        let (_websocket, _) = client.unwrap();
+        let server = server.unwrap();
+        assert!(server.is_running());
+        assert_eq!(server.connection_info().peer_addr, None);
+    }
+
+    #[tokio::test]
+    async fn connect_records_the_peer_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = listener.local_addr().unwrap();
+        let game = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio_tungstenite::accept_async(stream).await.unwrap()
+        };
+
+        let (server, _websocket) =
+            tokio::join!(Server::connect(format!("ws://{}", server_addr)), game);
+        assert_eq!(
+            server.unwrap().connection_info().peer_addr,
+            Some(server_addr)
+        );
+    }
+
+    #[tokio::test]
+    async fn negotiated_version_follows_the_peer() {
+        let (server, mut client) = connect().await;
+        assert_eq!(server.connection_info().negotiated_version, 1);
+
+        let client_side = async {
+            let request = client.recv().await;
+            let mut response = response(request_id(&request), 0, json!({}));
+            response["header"]["version"] = json!(26);
+            client.send(response).await;
+        };
+        let (response, _) =
+            tokio::join!(server.send_command(SayCommand::new("hello")), client_side);
+
+        assert!(response.is_ok());
+        assert_eq!(server.connection_info().negotiated_version, 26);
+    }
+
+    #[tokio::test]
+    async fn fetch_all_commands_merges_pages() {
+        let (server, mut client) = connect().await;
+
+        let client_side = client.answer_help(&[
+            "/say <message: message>\n/kill [target: target]",
+            "/list\n/say <message: string>",
+            "/kill [target: target]\n/weather <clear|rain|thunder>",
+        ]);
+        let (commands, _) = tokio::join!(server.fetch_all_commands(), client_side);
+
+        let names: Vec<String> = commands
+            .unwrap()
+            .into_iter()
+            .map(|command| command.name)
+            .collect();
+        assert_eq!(names, ["say", "kill", "list", "weather"]);
+    }
+
+    #[tokio::test]
+    async fn fetch_all_commands_stops_at_the_page_count() {
+        let (server, mut client) = connect().await;
+
+        let (commands, _) = tokio::join!(
+            server.fetch_all_commands(),
+            client.answer_help(&["/list\n/help [page: int]"]),
+        );
+
+        assert_eq!(commands.unwrap().len(), 2);
+        assert!(client.is_quiet_for(Duration::from_millis(50)).await);
+    }
+
+    #[tokio::test]
+    async fn run_all_returns_the_first_handler_error() {
+        let (websocket, _client) = websocket_pair().await;
+        let handler = |result: Result<()>| move |_handle: ServerHandle| async move { result };
+
+        let result = Server::run_all(
+            websocket,
+            vec![
+                handler(Ok(())),
+                handler(Err(Error::IdleTimeout)),
+                handler(Err(Error::LoopNotRunning)),
+            ],
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(MultiError::HandlerErrored(Error::IdleTimeout))
+        ));
+    }
+
+    #[tokio::test]
+    async fn local_player_name_is_fetched_once() {
+        let (mut server, mut client) = connect().await;
+
+        let (name, request) = tokio::join!(
+            server.local_player_name(),
+            client.answer_command(json!({ "localplayername": "Steve" })),
+        );
+        assert_eq!(name.unwrap(), "Steve");
+        assert_eq!(request["body"]["commandLine"], "getlocalplayername");
+
+        assert_eq!(server.local_player_name().await.unwrap(), "Steve");
+        assert!(client.is_quiet_for(Duration::from_millis(50)).await);
+    }
+
+    #[tokio::test]
+    async fn taken_loop_errors_are_not_reported_as_clean_closes() {
+        let config = ServerConfig::default().with_idle_timeout(Some(Duration::from_millis(20)));
+        let (mut server, _client) = connect_with_config(config).await;
+        while server.is_running() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert!(matches!(server.get_loop_result(), Some(Error::IdleTimeout)));
+        assert!(matches!(
+            server.wait_until_closed().await,
+            Err(Error::LoopErrorTaken)
+        ));
+        assert!(server.get_loop_result().is_none());
+    }
+
+    #[tokio::test]
+    async fn wait_until_closed_reports_its_error_once() {
+        let config = ServerConfig::default().with_idle_timeout(Some(Duration::from_millis(20)));
+        let (mut server, _client) = connect_with_config(config).await;
+
+        assert!(matches!(
+            server.wait_until_closed().await,
+            Err(Error::IdleTimeout)
+        ));
+        assert!(matches!(
+            server.wait_until_closed().await,
+            Err(Error::LoopErrorTaken)
+        ));
+    }
+
+    #[tokio::test]
+    async fn strict_headers_skip_packets_with_unknown_headers() {
+        let (hooks, errors) = error_log();
+        let config = ServerConfig::default()
+            .with_strict_headers(true)
+            .with_hooks(hooks);
+        let (server, mut client) = connect_with_config(config).await;
+
+        let client_side = async {
+            let request = client.recv().await;
+            let mut drifted = response(request_id(&request), 0, json!({ "message": "drifted" }));
+            drifted["header"]["newHeader"] = json!(true);
+            client.send(drifted).await;
+            client
+                .send(response(
+                    request_id(&request),
+                    0,
+                    json!({ "message": "hello" }),
+                ))
+                .await;
+        };
+        let (response, _) =
+            tokio::join!(server.send_command(SayCommand::new("hello")), client_side);
+
+        // The body's command data is never checked.
+        assert_eq!(response.unwrap().message.as_deref(), Some("hello"));
+        assert_eq!(
+            *errors.lock().unwrap(),
+            ["unknown header entries: newHeader"]
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_headers_are_kept_by_default() {
+        let (server, mut client) = connect().await;
+
+        let client_side = async {
+            let request = client.recv().await;
+            let mut drifted = response(request_id(&request), 0, json!({ "message": "hello" }));
+            drifted["header"]["newHeader"] = json!(true);
+            client.send(drifted).await;
+        };
+        let (response, _) = tokio::join!(
+            server.send_raw_command(SayCommand::new("hello").into()),
+            client_side
+        );
+
+        assert_eq!(response.unwrap().extra_header["newHeader"], true);
+    }
+
+    fn error_packet(request_id: Option<&str>) -> serde_json::Value {
+        let mut packet = json!({
+            "header": { "version": 1, "messagePurpose": "error" },
+            "body": { "statusCode": -1, "statusMessage": "Syntax error" },
+        });
+        if let Some(request_id) = request_id {
+            packet["header"]["requestId"] = json!(request_id);
+        }
+        packet
+    }
+
+    #[tokio::test]
+    async fn error_packets_fail_only_their_command() {
+        let (server, mut client) = connect().await;
+
+        let client_side = async {
+            let first = client.recv().await;
+            let second = client.recv().await;
+            client.send(error_packet(Some(request_id(&first)))).await;
+            client
+                .send(response(
+                    request_id(&second),
+                    0,
+                    json!({ "message": "second" }),
+                ))
+                .await;
+        };
+        let (first, second, _) = tokio::join!(
+            server.send_command(SayCommand::new("first")),
+            server.send_command(SayCommand::new("second")),
+            client_side,
+        );
+
+        assert!(matches!(
+            first,
+            Err(Error::MinecraftError {
+                status_code: -1,
+                ..
+            })
+        ));
+        assert_eq!(second.unwrap().message.as_deref(), Some("second"));
+    }
+
+    #[tokio::test]
+    async fn uncorrelated_error_packets_are_reported() {
+        let (hooks, errors) = error_log();
+        let (server, mut client) =
+            connect_with_config(ServerConfig::default().with_hooks(hooks)).await;
+
+        client.send(error_packet(None)).await;
+        let (response, _) = tokio::join!(
+            server.send_command(SayCommand::new("hello")),
+            client.answer_command(json!({ "message": "hello" })),
+        );
+
+        assert!(response.is_ok());
+        assert!(server.is_running());
+        assert_eq!(
+            *errors.lock().unwrap(),
+            [r#"minecraft error with status: -1 Some("Syntax error")"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn fragmented_messages_are_reassembled() {
+        let (server, mut client) = connect().await;
+
+        let client_side = async {
+            let request = client.recv().await;
+            let text =
+                response(request_id(&request), 0, json!({ "message": "in pieces" })).to_string();
+            let (first, rest) = text.as_bytes().split_at(text.len() / 3);
+            let (second, third) = rest.split_at(rest.len() / 2);
+
+            let text_frame = OpCode::Data(Data::Text);
+            let continuation = OpCode::Data(Data::Continue);
+            client
+                .send_frame(Message::Frame(Frame::message(
+                    first.to_vec(),
+                    text_frame,
+                    false,
+                )))
+                .await;
+            // Control frames may arrive between the pieces of a message.
+            client.send_frame(Message::Ping(vec![])).await;
+            client
+                .send_frame(Message::Frame(Frame::message(
+                    second.to_vec(),
+                    continuation,
+                    false,
+                )))
+                .await;
+            client
+                .send_frame(Message::Frame(Frame::message(
+                    third.to_vec(),
+                    continuation,
+                    true,
+                )))
+                .await;
+        };
+        let (response, _) =
+            tokio::join!(server.send_command(SayCommand::new("hello")), client_side);
+
+        assert_eq!(response.unwrap().message.as_deref(), Some("in pieces"));
+    }
 }
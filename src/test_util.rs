@@ -0,0 +1,162 @@
+// An in-memory game client for exercising a `Server` without a real connection.
+use crate::{
+    event::Event,
+    packet::{CommandRequestPacket, CommandResponsePacket, Packet},
+    Hooks, Server, ServerConfig,
+};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::io::DuplexStream;
+use tokio_tungstenite::{
+    tungstenite::{protocol::Role, Message},
+    WebSocketStream,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(crate) struct Client {
+    stream: WebSocketStream<DuplexStream>,
+}
+
+impl Client {
+    // The next text frame sent by the server, parsed as JSON.
+    pub(crate) async fn recv(&mut self) -> Value {
+        loop {
+            if let Message::Text(text) = self.recv_frame().await {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    pub(crate) async fn recv_frame(&mut self) -> Message {
+        tokio::time::timeout(TIMEOUT, self.stream.next())
+            .await
+            .expect("timed out waiting for the server")
+            .expect("server closed the connection")
+            .unwrap()
+    }
+
+    // Whether the server sends anything within `duration`.
+    pub(crate) async fn is_quiet_for(&mut self, duration: Duration) -> bool {
+        tokio::time::timeout(duration, self.stream.next())
+            .await
+            .is_err()
+    }
+
+    pub(crate) async fn send(&mut self, value: Value) {
+        self.send_frame(Message::Text(value.to_string())).await;
+    }
+
+    pub(crate) async fn send_frame(&mut self, message: Message) {
+        self.stream.send(message).await.unwrap();
+    }
+
+    // Waits for a command request and answers it with `body` plus a status code of 0.
+    pub(crate) async fn answer_command(&mut self, body: Value) -> Value {
+        let request = self.recv().await;
+        self.send(response(request_id(&request), 0, body)).await;
+        request
+    }
+
+    // Answers `help <page>` requests, in whatever order they come, until every one of
+    // `pages` has been sent once.
+    pub(crate) async fn answer_help(&mut self, pages: &[&str]) {
+        for _ in pages {
+            let request = self.recv().await;
+            let page: usize = request["body"]["commandLine"]
+                .as_str()
+                .unwrap()
+                .trim_start_matches("help ")
+                .parse()
+                .unwrap();
+            let body = json!({ "body": pages[page - 1], "page": page, "pageCount": pages.len() });
+            self.send(response(request_id(&request), 0, body)).await;
+        }
+    }
+}
+
+pub(crate) async fn connect() -> (Server, Client) {
+    connect_with_config(ServerConfig::default()).await
+}
+
+pub(crate) async fn connect_with_config(config: ServerConfig) -> (Server, Client) {
+    let (server, client) = websocket_pair().await;
+    (Server::spawn_with_config(server, config), client)
+}
+
+// The server's end is left unspawned, for entry points that spawn it themselves.
+pub(crate) async fn websocket_pair() -> (WebSocketStream<DuplexStream>, Client) {
+    let (server, client) = tokio::io::duplex(1 << 20);
+    let server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+    let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+
+    (server, Client { stream: client })
+}
+
+pub(crate) fn request_id(packet: &Value) -> &str {
+    packet["header"]["requestId"].as_str().unwrap()
+}
+
+pub(crate) fn response(request_id: &str, status_code: i32, body: Value) -> Value {
+    let mut packet = json!({
+        "header": {
+            "version": 1,
+            "messagePurpose": "commandResponse",
+            "requestId": request_id,
+        },
+        "body": { "statusCode": status_code },
+    });
+
+    if let Value::Object(body) = body {
+        packet["body"].as_object_mut().unwrap().extend(body);
+    }
+    packet
+}
+
+// A reply as most commands give it, with nothing besides a status.
+pub(crate) fn status_reply(status_code: i32, status_message: &str) -> CommandResponsePacket {
+    CommandResponsePacket {
+        status_code,
+        status_message: Some(status_message.to_string()),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn command_line<T: Into<CommandRequestPacket>>(command: T) -> String {
+    command.into().command_line
+}
+
+pub(crate) fn event(event_name: &str, body: Value) -> Value {
+    json!({
+        "header": {
+            "version": 1,
+            "messagePurpose": "event",
+            "eventName": event_name,
+        },
+        "body": body,
+    })
+}
+
+// Reads `body` the way an event packet carrying it off the wire would be.
+pub(crate) fn parse_event<T: Event>(body: Value) -> T {
+    let json = event(&format!("{:?}", T::get_type()), body).to_string();
+    match Packet::from_json(&json).unwrap() {
+        Packet::Event(event) => event.deserialize_as().unwrap(),
+        other => panic!("expected an event, got {other}"),
+    }
+}
+
+// Hooks recording every error the loop reports, as their messages.
+pub(crate) fn error_log() -> (Hooks, Arc<Mutex<Vec<String>>>) {
+    let errors = Arc::new(Mutex::new(vec![]));
+    let hooks = {
+        let errors = errors.clone();
+        Hooks::default().with_error(move |error| errors.lock().unwrap().push(error.to_string()))
+    };
+
+    (hooks, errors)
+}
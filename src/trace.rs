@@ -0,0 +1,18 @@
+// Thin wrappers so instrumentation compiles away when the `tracing` feature is off.
+
+macro_rules! trace_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!($($arg)*);
+    };
+}
+
+macro_rules! trace_warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::warn!($($arg)*);
+    };
+}
+
+pub(crate) use trace_debug as debug;
+pub(crate) use trace_warn as warn;
@@ -0,0 +1,51 @@
+use crate::Result;
+use tokio::io::{AsyncRead, AsyncWrite};
+pub use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::WebSocketStream;
+
+// Large `help` pages and `querytarget` replies can outgrow tungstenite's default message
+// and frame limits; this is `Server::accept` with them exposed.
+pub async fn accept_with_config<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    config: WebSocketConfig,
+) -> Result<WebSocketStream<S>> {
+    Ok(tokio_tungstenite::accept_async_with_config(stream, Some(config)).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::{error::CapacityError, Error, Message};
+
+    #[tokio::test]
+    async fn the_message_size_limit_applies() {
+        let (server, client) = tokio::io::duplex(1 << 16);
+        let config = WebSocketConfig {
+            max_message_size: Some(64),
+            ..Default::default()
+        };
+
+        let (server, client) = tokio::join!(
+            accept_with_config(server, config),
+            tokio_tungstenite::client_async("ws://localhost", client),
+        );
+        let mut server = server.unwrap();
+        let (mut client, _) = client.unwrap();
+
+        client
+            .send(Message::Text("small".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            Message::Text("small".to_string())
+        );
+
+        client.send(Message::Text("x".repeat(1024))).await.unwrap();
+        assert!(matches!(
+            server.next().await,
+            Some(Err(Error::Capacity(CapacityError::MessageTooLong { .. })))
+        ));
+    }
+}